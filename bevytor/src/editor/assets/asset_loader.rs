@@ -1,11 +1,18 @@
-use crate::editor::EditorStateLabel;
+use crate::editor::assets::asset_types::{
+    AssetTypeRegistration, AssetTypeRegistry, RegisterAssetType,
+};
+use crate::editor::assets::font::render_font_sample;
+use crate::editor::assets::ignore_rules::AssetIgnoreRules;
+use crate::editor::assets::meta::{AssetGuidIndex, AssetReference};
+use crate::editor::assets::scan::{asset_scan_system, start_asset_scan_system};
+use bevy::gltf::Gltf;
 use bevy::prelude::*;
 use bevy_egui::egui::TextureId;
 use bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
 use std::env;
-use std::ffi::{OsStr, OsString};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
 /// AssetLoaderPlugin iterates over bevys "asset" folder creating directory hierarchy
 /// and loading all supported assets
@@ -24,11 +31,20 @@ impl Plugin for AssetLoaderPlugin {
             .join(ASSET_DIRECTORY_NAME);
         let root = AssetDirectory::new(asset_dir.clone());
 
-        app.insert_resource(root).add_startup_system_set(
-            SystemSet::new()
-                .with_system(load_editor_assets_system)
-                .with_system(load_assets_system),
-        );
+        app.insert_resource(root)
+            .init_resource::<AssetGuidIndex>()
+            .register_type::<AssetReference>()
+            .register_asset_type(image_asset_type())
+            .register_asset_type(scene_asset_type())
+            .register_asset_type(model_asset_type())
+            .register_asset_type(audio_asset_type())
+            .register_asset_type(font_asset_type())
+            .register_asset_type(shader_asset_type())
+            .register_asset_type(text_asset_type())
+            .insert_resource(EditorAssets::default())
+            .add_startup_system(load_editor_assets_system)
+            .add_startup_system(start_asset_scan_system.after(load_editor_assets_system))
+            .add_system(asset_scan_system);
     }
 }
 
@@ -60,7 +76,7 @@ impl AssetDescriptor for ImageAssetDescriptor {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneAssetDescriptor {
     /// Name of the asset, with extension
     pub name: OsString,
@@ -78,53 +94,301 @@ impl AssetDescriptor for SceneAssetDescriptor {
     }
 }
 
-/// All asset types currently supported in Bevytor. This enum will grow over time and at some
-/// point it will be moved to separate module
+/// glTF/GLB model descriptor. Sub-assets are available through `Assets<Gltf>` once loaded
+#[derive(Debug, Clone)]
+pub struct ModelAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+    /// Bevy asset handle
+    pub bevy_handle: Handle<Gltf>,
+}
+
+impl AssetDescriptor for ModelAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// Audio file descriptor. Audio is loaded by bevy only when it is previewed
+#[derive(Debug, Clone)]
+pub struct AudioAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+}
+
+impl AssetDescriptor for AudioAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// Font descriptor. Thumbnail shows sample text rendered with the font
+#[derive(Debug, Clone)]
+pub struct FontAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+    /// Rendered sample text, None if the font couldn't be rendered
+    pub thumbnail_handle: Option<Handle<Image>>,
+    /// Egui texture id of the thumbnail
+    pub egui_texture_id: Option<TextureId>,
+}
+
+impl AssetDescriptor for FontAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShaderAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+}
+
+impl AssetDescriptor for ShaderAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// Plain text or config file (RON, JSON)
+#[derive(Debug, Clone)]
+pub struct TextAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+}
+
+impl AssetDescriptor for TextAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// Descriptor of assets registered by other plugins through the AssetTypeRegistry
+#[derive(Debug, Clone)]
+pub struct GenericAssetDescriptor {
+    /// Name of the asset, with extension
+    pub name: OsString,
+    /// Path relative to assets directory
+    pub path: PathBuf,
+    /// Name of the registered asset type
+    pub type_name: &'static str,
+    /// Bevy asset handle, if the asset is loaded by bevy
+    pub bevy_handle: Option<HandleUntyped>,
+}
+
+impl GenericAssetDescriptor {
+    pub fn new(path: &Path, type_name: &'static str) -> Self {
+        Self {
+            name: path.file_name().unwrap().to_os_string(),
+            path: path.to_path_buf(),
+            type_name,
+            bevy_handle: None,
+        }
+    }
+}
+
+impl AssetDescriptor for GenericAssetDescriptor {
+    fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// All asset types currently supported in Bevytor. Types without dedicated variant are
+/// represented by `Other` and described by their AssetTypeRegistration
 #[derive(Debug, Clone)]
 pub enum AssetType {
     Image(ImageAssetDescriptor),
     Scene(SceneAssetDescriptor),
+    Model(ModelAssetDescriptor),
+    Audio(AudioAssetDescriptor),
+    Font(FontAssetDescriptor),
+    Shader(ShaderAssetDescriptor),
+    Text(TextAssetDescriptor),
+    Other(GenericAssetDescriptor),
 }
 
-impl AssetType {
-    /// Try create an asset from given path. Naive implementation
-    /// TODO: Make extension detection more sophisticated
-    fn try_create(
-        path: &Path,
-        asset_server: &AssetServer,
-        egui_context: &mut EguiContext,
-    ) -> Option<Self> {
-        match path.extension().and_then(OsStr::to_str) {
-            None => None,
-            Some(extension) => {
-                const IMAGE_EXTENSIONS: &[&str] = &["png", "hdr"];
-                const SCENE_EXTENSIONS: &[&str] = &["ron"];
-                let name = path.file_name().unwrap().to_os_string();
-                let path = path.to_path_buf();
-
-                if IMAGE_EXTENSIONS.contains(&extension) {
-                    let bevy_handle = asset_server.load(path.clone());
-                    let egui_texture_id = egui_context.add_image(bevy_handle.as_weak());
-
-                    Some(Self::Image(ImageAssetDescriptor {
-                        name,
-                        path,
-                        bevy_handle,
-                        egui_texture_id,
-                    }))
-                } else if SCENE_EXTENSIONS.contains(&extension) {
-                    Some(Self::Scene(SceneAssetDescriptor { name, path }))
-                } else {
-                    None
-                }
-            }
+pub const IMAGE_ASSET_TYPE: &str = "Image";
+pub const SCENE_ASSET_TYPE: &str = "Scene";
+pub const MODEL_ASSET_TYPE: &str = "Model";
+pub const AUDIO_ASSET_TYPE: &str = "Audio";
+pub const FONT_ASSET_TYPE: &str = "Font";
+pub const SHADER_ASSET_TYPE: &str = "Shader";
+pub const TEXT_ASSET_TYPE: &str = "Text";
+
+/// Registration of images, thumbnail is the image itself
+pub fn image_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(IMAGE_ASSET_TYPE, &["png", "hdr"], |path, context| {
+        let bevy_handle = context.asset_server.load(path);
+        let egui_texture_id = context.egui_context.add_image(bevy_handle.as_weak());
+        Some(AssetType::Image(ImageAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+            bevy_handle,
+            egui_texture_id,
+        }))
+    })
+    .with_thumbnail(|asset| match asset {
+        AssetType::Image(image) => Some(image.egui_texture_id),
+        _ => None,
+    })
+}
+
+/// Registration of scenes
+pub fn scene_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(SCENE_ASSET_TYPE, &["scn.ron"], |path, _| {
+        Some(AssetType::Scene(SceneAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+        }))
+    })
+    .with_icon("scene.png")
+}
+
+/// Registration of glTF models
+pub fn model_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(MODEL_ASSET_TYPE, &["gltf", "glb"], |path, context| {
+        Some(AssetType::Model(ModelAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+            bevy_handle: context.asset_server.load(path),
+        }))
+    })
+    .with_icon("model.png")
+}
+
+/// Registration of audio files
+pub fn audio_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(
+        AUDIO_ASSET_TYPE,
+        &["ogg", "wav", "mp3", "flac"],
+        |path, _| {
+            Some(AssetType::Audio(AudioAssetDescriptor {
+                name: path.file_name()?.to_os_string(),
+                path: path.to_path_buf(),
+            }))
+        },
+    )
+    .with_icon("audio.png")
+}
+
+/// Registration of fonts, thumbnail is rendered when the font is found
+pub fn font_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(FONT_ASSET_TYPE, &["ttf", "otf"], |path, context| {
+        let thumbnail_handle = std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(render_font_sample)
+            .map_err(|e| warn!("Failed to render font {:?}: {}", path, e))
+            .ok()
+            .map(|thumbnail| context.images.add(thumbnail));
+        let egui_texture_id = thumbnail_handle
+            .as_ref()
+            .map(|handle| context.egui_context.add_image(handle.as_weak()));
+        Some(AssetType::Font(FontAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+            thumbnail_handle,
+            egui_texture_id,
+        }))
+    })
+    .with_thumbnail(|asset| match asset {
+        AssetType::Font(font) => font.egui_texture_id,
+        _ => None,
+    })
+    .with_icon("font.png")
+}
+
+/// Registration of WGSL shaders
+pub fn shader_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(SHADER_ASSET_TYPE, &["wgsl"], |path, _| {
+        Some(AssetType::Shader(ShaderAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+        }))
+    })
+    .with_icon("shader.png")
+}
+
+/// Registration of plain text and config files. Scenes saved with the plain `.ron` extension
+/// by older editor versions are still recognized as scenes
+pub fn text_asset_type() -> AssetTypeRegistration {
+    AssetTypeRegistration::new(TEXT_ASSET_TYPE, &["txt", "ron", "json"], |path, _| {
+        let name = path.file_name()?.to_os_string();
+        let is_ron = path.extension().is_some_and(|extension| extension == "ron");
+        if is_ron && is_scene_file(path) {
+            info!("{:?} is a scene, rename it to *.scn.ron", path);
+            return Some(AssetType::Scene(SceneAssetDescriptor {
+                name,
+                path: path.to_path_buf(),
+            }));
         }
+        Some(AssetType::Text(TextAssetDescriptor {
+            name,
+            path: path.to_path_buf(),
+        }))
+    })
+    .with_icon("text.png")
+}
+
+/// Returns true if the RON file holds a serialized DynamicScene, i.e. a list of entities
+fn is_scene_file(path: &Path) -> bool {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct SceneEntity {
+        entity: u32,
+        components: Vec<ron::Value>,
     }
 
+    std::fs::read_to_string(path)
+        .map(|content| ron::from_str::<Vec<SceneEntity>>(&content).is_ok())
+        .unwrap_or(false)
+}
+
+impl AssetType {
     pub fn get_path(&self) -> PathBuf {
         match self {
             AssetType::Image(asset_descriptor) => asset_descriptor.get_path(),
             AssetType::Scene(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Model(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Audio(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Font(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Shader(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Text(asset_descriptor) => asset_descriptor.get_path(),
+            AssetType::Other(asset_descriptor) => asset_descriptor.get_path(),
         }
     }
 
@@ -132,6 +396,26 @@ impl AssetType {
         match self {
             AssetType::Image(asset_descriptor) => asset_descriptor.get_name(),
             AssetType::Scene(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Model(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Audio(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Font(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Shader(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Text(asset_descriptor) => asset_descriptor.get_name(),
+            AssetType::Other(asset_descriptor) => asset_descriptor.get_name(),
+        }
+    }
+
+    /// Name of the AssetTypeRegistration describing this asset
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AssetType::Image(_) => IMAGE_ASSET_TYPE,
+            AssetType::Scene(_) => SCENE_ASSET_TYPE,
+            AssetType::Model(_) => MODEL_ASSET_TYPE,
+            AssetType::Audio(_) => AUDIO_ASSET_TYPE,
+            AssetType::Font(_) => FONT_ASSET_TYPE,
+            AssetType::Shader(_) => SHADER_ASSET_TYPE,
+            AssetType::Text(_) => TEXT_ASSET_TYPE,
+            AssetType::Other(asset_descriptor) => asset_descriptor.type_name,
         }
     }
 }
@@ -166,24 +450,109 @@ impl AssetDirectory {
         }
     }
 
-    /// Checks if path is supported asset or directory and adds it to proper category
-    /// and inserts it if so
-    fn try_insert(
+    /// Insert directory or asset created by `create_asset` into the hierarchy.
+    /// Inserting already present path is a no-op. Returns true if the hierarchy changed
+    pub fn insert_path(
         &mut self,
         path: &Path,
-        asset_server: &AssetServer,
-        mut egui_context: &mut EguiContext,
+        create_asset: &mut dyn FnMut(&Path) -> Option<AssetType>,
     ) -> bool {
-        if path.metadata().unwrap().is_dir() {
+        if self.contains(path) {
+            false
+        } else if path.is_dir() {
             self.try_insert_directory(AssetDirectory::new(path.to_path_buf()))
                 .is_ok()
-        } else if let Some(asset) = AssetType::try_create(path, asset_server, &mut egui_context) {
+        } else if let Some(asset) = create_asset(path) {
             self.try_insert_asset(asset).is_ok()
         } else {
             false
         }
     }
 
+    /// Insert given path and, if it is a directory, all of its content except ignored entries.
+    /// Returns paths that were actually inserted
+    pub fn insert_recursive(
+        &mut self,
+        path: &Path,
+        create_asset: &mut dyn FnMut(&Path) -> Option<AssetType>,
+        ignore_rules: &mut AssetIgnoreRules,
+    ) -> Vec<PathBuf> {
+        ignore_rules
+            .walk(path)
+            .filter(|entry| self.insert_path(entry.path(), create_asset))
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+
+    /// Remove directory or asset under given path from the hierarchy.
+    /// Returns true if it was found
+    pub fn remove_path(&mut self, path: &Path) -> bool {
+        let parent = match path
+            .parent()
+            .and_then(|parent| self.find_by_path_mut(parent))
+        {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let count = parent.children_directories.len() + parent.assets.len();
+        parent
+            .children_directories
+            .retain(|child| child.path != path);
+        parent.assets.retain(|asset| asset.get_path() != path);
+        count != parent.children_directories.len() + parent.assets.len()
+    }
+
+    /// Checks if directory or asset under given path is part of the hierarchy
+    pub fn contains(&self, path: &Path) -> bool {
+        path.parent()
+            .and_then(|parent| self.find_by_path(parent))
+            .is_some_and(|parent| {
+                parent
+                    .children_directories
+                    .iter()
+                    .any(|child| child.path == path)
+                    || parent.assets.iter().any(|asset| asset.get_path() == path)
+            })
+    }
+
+    /// Find asset by its path
+    pub fn find_asset(&self, path: &Path) -> Option<&AssetType> {
+        self.find_by_path(path.parent()?)?
+            .assets
+            .iter()
+            .find(|asset| asset.get_path() == path)
+    }
+
+    /// Directories and assets of the hierarchy matching the ignore rules.
+    /// Content of ignored directories is not listed separately
+    pub fn ignored_paths(&self, ignore_rules: &AssetIgnoreRules) -> Vec<PathBuf> {
+        let mut ignored = Vec::new();
+        for child in self.children_directories.iter() {
+            match ignore_rules.is_ignored(&child.path, true) {
+                true => ignored.push(child.path.clone()),
+                false => ignored.extend(child.ignored_paths(ignore_rules)),
+            }
+        }
+        ignored.extend(
+            self.assets
+                .iter()
+                .map(AssetType::get_path)
+                .filter(|path| ignore_rules.is_ignored(path, false)),
+        );
+        ignored
+    }
+
+    /// All assets in this directory and its subdirectories
+    pub fn iter_assets(&self) -> Box<dyn Iterator<Item = &AssetType> + '_> {
+        Box::new(
+            self.assets.iter().chain(
+                self.children_directories
+                    .iter()
+                    .flat_map(|child| child.iter_assets()),
+            ),
+        )
+    }
+
     /// Checks if given directory is child of any directory in the hierarchy and
     /// stores it if it's true. Returns given directory back in case of error
     fn try_insert_directory(
@@ -234,7 +603,7 @@ impl AssetDirectory {
         pred: &dyn Fn(&AssetDirectory) -> bool,
     ) -> Option<&AssetDirectory> {
         if pred(self) {
-            return Some(&self);
+            return Some(self);
         } else {
             for child in self.children_directories.iter() {
                 if let Some(result) = child.find_by_predicate(pred) {
@@ -246,23 +615,39 @@ impl AssetDirectory {
         None
     }
 
-    /// Find directory by path. Convenience fn using `find_by_predicate` underneath
-    pub fn find_by_path(&self, path: &PathBuf) -> Option<&AssetDirectory> {
-        self.find_by_predicate(&|dir: &AssetDirectory| dir.path == *path)
+    /// Find directory by path. Only descends into directories containing the path
+    pub fn find_by_path(&self, path: &Path) -> Option<&AssetDirectory> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children_directories
+            .iter()
+            .find(|child| path.starts_with(&child.path))?
+            .find_by_path(path)
+    }
+
+    /// Mutable version of `find_by_path`
+    pub fn find_by_path_mut(&mut self, path: &Path) -> Option<&mut AssetDirectory> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children_directories
+            .iter_mut()
+            .find(|child| path.starts_with(&child.path))?
+            .find_by_path_mut(path)
     }
 
     /// Find directory that satisfies given predicate
     pub fn find_by_predicate_mut(
         &mut self,
-        pred: impl Fn(&mut AssetDirectory) -> bool,
+        pred: &dyn Fn(&AssetDirectory) -> bool,
     ) -> Option<&mut AssetDirectory> {
         if pred(self) {
             return Some(self);
-        } else {
-            for child in self.children_directories.iter_mut() {
-                if pred(child) {
-                    return Some(child);
-                }
+        }
+        for child in self.children_directories.iter_mut() {
+            if let Some(result) = child.find_by_predicate_mut(pred) {
+                return Some(result);
             }
         }
 
@@ -283,8 +668,8 @@ impl AssetDirectory {
 #[derive(Default)]
 pub struct EditorAssets {
     pub directory_icon: TextureId,
-    pub map_icon: TextureId,
-    pub map_icon_handle: Handle<Image>,
+    /// Icon of assets whose type doesn't provide one
+    pub file_icon: TextureId,
 }
 
 /// Load assets commonly used around the editor
@@ -293,6 +678,7 @@ pub fn load_editor_assets_system(
     mut commands: Commands,
     mut egui_context: ResMut<EguiContext>,
     asset_server: ResMut<AssetServer>,
+    mut asset_types: ResMut<AssetTypeRegistry>,
 ) {
     println!("Loading editor assets");
     const EDITOR_ASSETS_DIRECTORY: &str = "assets";
@@ -303,30 +689,42 @@ pub fn load_editor_assets_system(
         .join(EDITOR_ASSETS_DIRECTORY);
     let directory_icon_handle: Handle<Image> =
         asset_server.load(editor_assets_dir.join("directory.png").as_path());
-    let map_icon_handle: Handle<Image> =
-        asset_server.load(editor_assets_dir.join("scene.png").as_path());
+    let file_icon_handle: Handle<Image> =
+        asset_server.load(editor_assets_dir.join("file.png").as_path());
+    for registration in asset_types.iter_mut() {
+        if let Some(icon) = &registration.icon {
+            let icon_handle: Handle<Image> = asset_server.load(editor_assets_dir.join(icon));
+            registration.icon_texture = Some(egui_context.add_image(icon_handle));
+        }
+    }
     let editor_assets = EditorAssets {
         directory_icon: egui_context.add_image(directory_icon_handle),
-        map_icon: egui_context.add_image(map_icon_handle.clone().as_weak()),
-        map_icon_handle,
+        file_icon: egui_context.add_image(file_icon_handle),
     };
     commands.insert_resource(editor_assets);
 }
 
-/// Load assets stored in the game assets directory
-/// TODO: Consider moving this system to build fn
-pub fn load_assets_system(
-    asset_server: Res<AssetServer>,
-    mut egui_ctx: ResMut<EguiContext>,
-    mut root: ResMut<AssetDirectory>,
-    mut editor_state: ResMut<EditorStateLabel>,
-) {
-    println!("Loading assets");
-    for entry in WalkDir::new(root.path.clone())
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        root.try_insert(entry.path(), &asset_server, &mut egui_ctx);
-    }
-    *editor_state = EditorStateLabel::PostInitializingAssets;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::reflect::TypeRegistry;
+
+    #[test]
+    fn ron_scenes_are_recognized_by_content() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let registry = TypeRegistry::default();
+        let scene = DynamicScene::from_world(&World::new(), &registry);
+        let empty = temp_dir.path().join("empty.ron");
+        std::fs::write(&empty, scene.serialize_ron(&registry).unwrap()).unwrap();
+        let level = temp_dir.path().join("level.ron");
+        let entity = r#"[(entity: 0, components: [{"type": "bevy_core::name::Name"}])]"#;
+        std::fs::write(&level, entity).unwrap();
+        let config = temp_dir.path().join("config.ron");
+        std::fs::write(&config, "(volume: 0.5)").unwrap();
+
+        assert!(is_scene_file(&level));
+        assert!(is_scene_file(&empty));
+        assert!(!is_scene_file(&config));
+        assert!(!is_scene_file(&temp_dir.path().join("missing.ron")));
+    }
 }
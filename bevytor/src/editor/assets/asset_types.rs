@@ -1 +1,218 @@
+use crate::editor::assets::asset_loader::AssetType;
+use crate::editor::assets::meta::{AssetGuidIndex, AssetMeta};
+use bevy::prelude::*;
+use bevy::utils::Uuid;
+use bevy_egui::egui::TextureId;
+use bevy_egui::EguiContext;
+use std::path::{Path, PathBuf};
 
+/// Resources available to asset loaders
+pub struct AssetLoadContext<'a> {
+    pub asset_server: &'a AssetServer,
+    pub egui_context: &'a mut EguiContext,
+    /// Used for images generated by the editor, e.g. thumbnails
+    pub images: &'a mut Assets<Image>,
+    /// Every created asset is indexed by the GUID from its sidecar
+    pub guids: &'a mut AssetGuidIndex,
+}
+
+/// Creates asset descriptor of a file matching registered extensions.
+/// Returns None if the file can't be used as the asset after all
+pub type AssetLoaderFn =
+    Box<dyn Fn(&Path, &mut AssetLoadContext) -> Option<AssetType> + Send + Sync>;
+
+/// Returns asset specific thumbnail, e.g. the image itself. Icon is used when None
+pub type ThumbnailProviderFn = fn(&AssetType) -> Option<TextureId>;
+
+/// Description of a single asset type supported by the editor
+pub struct AssetTypeRegistration {
+    /// Unique name of the type, matches `AssetType::type_name`
+    pub name: &'static str,
+    /// Extensions without leading dot. Multi-part extensions (e.g. "scn.ron") are supported
+    pub extensions: Vec<&'static str>,
+    pub loader: AssetLoaderFn,
+    pub thumbnail: Option<ThumbnailProviderFn>,
+    /// Icon file name, relative to the editor assets directory
+    pub icon: Option<PathBuf>,
+    /// Icon texture, available once editor assets are loaded
+    pub icon_texture: Option<TextureId>,
+}
+
+impl AssetTypeRegistration {
+    pub fn new(
+        name: &'static str,
+        extensions: &[&'static str],
+        loader: impl Fn(&Path, &mut AssetLoadContext) -> Option<AssetType> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name,
+            extensions: extensions.to_vec(),
+            loader: Box::new(loader),
+            thumbnail: None,
+            icon: None,
+            icon_texture: None,
+        }
+    }
+
+    pub fn with_thumbnail(mut self, thumbnail: ThumbnailProviderFn) -> Self {
+        self.thumbnail = Some(thumbnail);
+        self
+    }
+
+    pub fn with_icon(mut self, icon: impl Into<PathBuf>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Length of the longest extension matching given file name
+    fn matching_extension_len(&self, file_name: &str) -> Option<usize> {
+        matching_extension_len(&self.extensions, file_name)
+    }
+}
+
+/// Length of the longest of the extensions matching given lowercase file name
+fn matching_extension_len(extensions: &[&str], file_name: &str) -> Option<usize> {
+    extensions
+        .iter()
+        .filter(|extension| {
+            file_name.len() > extension.len() + 1
+                && file_name.ends_with(*extension)
+                && file_name[..file_name.len() - extension.len()].ends_with('.')
+        })
+        .map(|extension| extension.len())
+        .max()
+}
+
+/// Checks if the file has any of the extensions, matched case insensitively
+pub fn has_extension(extensions: &[&str], path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(|file_name| matching_extension_len(extensions, &file_name.to_lowercase()))
+        .is_some()
+}
+
+/// Registry of asset types known to the editor. Plugins add their types through
+/// `register_asset_type`, asset loader and browser consult it for every file
+#[derive(Default)]
+pub struct AssetTypeRegistry {
+    types: Vec<AssetTypeRegistration>,
+}
+
+impl AssetTypeRegistry {
+    /// Register asset type. Registration with the same name replaces the previous one
+    pub fn register(&mut self, registration: AssetTypeRegistration) {
+        self.types.retain(|other| other.name != registration.name);
+        self.types.push(registration);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AssetTypeRegistration> {
+        self.types
+            .iter()
+            .find(|registration| registration.name == name)
+    }
+
+    /// Extensions of all the registered types
+    pub fn extensions(&self) -> Vec<&'static str> {
+        self.types
+            .iter()
+            .flat_map(|registration| registration.extensions.iter().copied())
+            .collect()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut AssetTypeRegistration> {
+        self.types.iter_mut()
+    }
+
+    /// Find type with the longest extension matching the file, so "level.scn.ron" prefers
+    /// "scn.ron" over "ron". Extensions are matched case insensitively
+    pub fn find_by_path(&self, path: &Path) -> Option<&AssetTypeRegistration> {
+        let file_name = path.file_name()?.to_str()?.to_lowercase();
+        self.types
+            .iter()
+            .filter_map(|registration| {
+                let length = registration.matching_extension_len(&file_name)?;
+                Some((length, registration))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, registration)| registration)
+    }
+
+    /// Create asset from given path using loader of the matching type
+    /// and register its GUID, creating the sidecar if needed
+    pub fn create(&self, path: &Path, context: &mut AssetLoadContext) -> Option<AssetType> {
+        self.create_and_register(path, context, |guids| guids.register(path))
+    }
+
+    /// Same as `create`, with the sidecar already read or created, e.g. by the asset scan task
+    pub fn create_with_meta(
+        &self,
+        path: &Path,
+        meta: Result<AssetMeta, String>,
+        context: &mut AssetLoadContext,
+    ) -> Option<AssetType> {
+        self.create_and_register(path, context, |guids| {
+            meta.and_then(|meta| guids.register_meta(path, meta))
+        })
+    }
+
+    fn create_and_register(
+        &self,
+        path: &Path,
+        context: &mut AssetLoadContext,
+        register: impl FnOnce(&mut AssetGuidIndex) -> Result<Uuid, String>,
+    ) -> Option<AssetType> {
+        let registration = self.find_by_path(path)?;
+        let asset = (registration.loader)(path, context)?;
+        if let Err(e) = register(context.guids) {
+            warn!("Failed to read meta file of {:?}: {}", path, e);
+        }
+        Some(asset)
+    }
+
+    /// Texture displayed for the asset: its thumbnail if available, icon of its type otherwise
+    pub fn texture_id(&self, asset: &AssetType) -> Option<TextureId> {
+        let registration = self.get(asset.type_name())?;
+        registration
+            .thumbnail
+            .and_then(|thumbnail| thumbnail(asset))
+            .or(registration.icon_texture)
+    }
+}
+
+/// App extension used by plugins for registering their asset types
+pub trait RegisterAssetType {
+    fn register_asset_type(&mut self, registration: AssetTypeRegistration) -> &mut Self;
+}
+
+impl RegisterAssetType for App {
+    fn register_asset_type(&mut self, registration: AssetTypeRegistration) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AssetTypeRegistry::default)
+            .register(registration);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(name: &'static str, extensions: &[&'static str]) -> AssetTypeRegistration {
+        AssetTypeRegistration::new(name, extensions, |_, _| None)
+    }
+
+    #[test]
+    fn longest_matching_extension_wins() {
+        let mut registry = AssetTypeRegistry::default();
+        registry.register(registration("Text", &["ron", "txt"]));
+        registry.register(registration("Scene", &["scn.ron"]));
+        let find = |path: &str| registry.find_by_path(Path::new(path)).map(|r| r.name);
+
+        assert_eq!(find("scenes/level.scn.ron"), Some("Scene"));
+        assert_eq!(find("scenes/LEVEL.SCN.RON"), Some("Scene"));
+        assert_eq!(find("config.ron"), Some("Text"));
+        assert_eq!(find("notes.md"), None);
+        assert_eq!(find("ron"), None);
+        assert_eq!(find("prison"), None);
+    }
+}
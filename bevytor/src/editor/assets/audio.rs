@@ -0,0 +1,101 @@
+use bevy::utils::HashMap;
+use rodio::{Decoder, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Properties of the audio file, read by decoding its header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioMetadata {
+    /// Computed by counting samples if the format doesn't store it in the header
+    pub duration: Duration,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl AudioMetadata {
+    /// Read metadata without playing the file, doesn't require an output device
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let decoder = Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let duration = match decoder.total_duration() {
+            Some(duration) => duration,
+            None => {
+                let frames = decoder.count() as f64 / channels.max(1) as f64;
+                Duration::from_secs_f64(frames / sample_rate.max(1) as f64)
+            }
+        };
+
+        Ok(Self {
+            duration,
+            channels,
+            sample_rate,
+        })
+    }
+}
+
+/// Resource caching audio metadata, so files are decoded only once.
+/// Metadata is read lazily, when audio asset is previewed for the first time
+#[derive(Default)]
+pub struct AudioMetadataCache {
+    entries: HashMap<PathBuf, Result<AudioMetadata, String>>,
+}
+
+impl AudioMetadataCache {
+    pub fn get_or_read(&mut self, path: &Path) -> &Result<AudioMetadata, String> {
+        self.entries
+            .entry(path.to_path_buf())
+            .or_insert_with(|| AudioMetadata::read(path))
+    }
+
+    /// Forget cached metadata of the file, or of all files in the directory
+    pub fn invalidate(&mut self, path: &Path) {
+        self.entries.retain(|cached, _| !cached.starts_with(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write silent 16 bit PCM wave file
+    fn write_wav(path: &Path, channels: u16, sample_rate: u32, frames: u32) {
+        let data_len = frames * channels as u32 * 2;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+        wav.extend_from_slice(&(channels * 2).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        std::fs::write(path, wav).unwrap();
+    }
+
+    #[test]
+    fn metadata_is_read_and_cached() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("step.wav");
+        write_wav(&path, 2, 8000, 4000);
+
+        let mut cache = AudioMetadataCache::default();
+        let metadata = cache.get_or_read(&path).clone().unwrap();
+        assert_eq!(metadata.channels, 2);
+        assert_eq!(metadata.sample_rate, 8000);
+        assert_eq!(metadata.duration, Duration::from_millis(500));
+
+        std::fs::write(&path, "not a wave file").unwrap();
+        assert!(cache.get_or_read(&path).is_ok());
+        cache.invalidate(&path);
+        assert!(cache.get_or_read(&path).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use crate::editor::assets::asset_loader::{AssetDirectory, AssetType};
+use crate::editor::assets::meta::AssetGuidIndex;
+use crate::editor::assets::watcher::{
+    asset_watcher_system, AssetAddedEvent, AssetModifiedEvent, AssetMovedEvent, AssetRemovedEvent,
+};
+use crate::editor::run_if_post_initializing_assets;
+use crate::editor::ui::notifications::EditorNotifications;
+use bevy::asset::SourcePathId;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Uuid};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// AssetDependencyPlugin keeps track of assets referenced by scenes
+pub struct AssetDependencyPlugin;
+impl Plugin for AssetDependencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetDependencyGraph>()
+            .add_event::<AssetAddedEvent>()
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
+            .add_event::<AssetModifiedEvent>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_post_initializing_assets)
+                    .with_system(update_dependency_graph_system.after(asset_watcher_system)),
+            );
+    }
+}
+
+/// Hashes of asset paths are stored in scenes as 64 bit numbers. Shorter numbers can't be
+/// a path hash in practice and are skipped
+const MIN_SOURCE_ID_DIGITS: usize = 10;
+
+/// Value found in scene RON which may refer to an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SceneToken {
+    /// String literal, e.g. asset path or GUID
+    Text(String),
+    /// Number, e.g. source path id of serialized handle
+    Number(u64),
+}
+
+/// Collect string literals and long numbers from the scene file
+fn tokenize(content: &str) -> Vec<SceneToken> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(character) = chars.next() {
+        if character == '"' {
+            let mut text = String::new();
+            while let Some(character) = chars.next() {
+                match character {
+                    '"' => break,
+                    '\\' => text.extend(chars.next()),
+                    _ => text.push(character),
+                }
+            }
+            tokens.push(SceneToken::Text(text));
+        } else if character.is_ascii_digit() {
+            let mut digits = String::from(character);
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            if digits.len() >= MIN_SOURCE_ID_DIGITS {
+                tokens.extend(digits.parse().ok().map(SceneToken::Number));
+            }
+        }
+    }
+    tokens
+}
+
+/// Number stored in scenes for handles loaded from given path
+fn source_path_id(path: &Path) -> Option<u64> {
+    let id = ron::to_string(&SourcePathId::from(path)).ok()?;
+    id.trim_matches(|c| c == '(' || c == ')').parse().ok()
+}
+
+/// Translates scene tokens into paths of assets present in AssetDirectory
+struct AssetPathResolver<'a> {
+    root: &'a AssetDirectory,
+    guids: &'a AssetGuidIndex,
+    source_ids: HashMap<u64, PathBuf>,
+}
+
+impl<'a> AssetPathResolver<'a> {
+    fn new(root: &'a AssetDirectory, guids: &'a AssetGuidIndex) -> Self {
+        let mut source_ids = HashMap::default();
+        for path in root.iter_assets().map(AssetType::get_path) {
+            // Assets can be loaded by both absolute path and path relative to the assets directory
+            let relative = path.strip_prefix(&root.path).ok().map(Path::to_path_buf);
+            for key in [Some(path.clone()), relative].into_iter().flatten() {
+                if let Some(id) = source_path_id(&key) {
+                    source_ids.insert(id, path.clone());
+                }
+            }
+        }
+        Self {
+            root,
+            guids,
+            source_ids,
+        }
+    }
+
+    fn resolve(&self, token: &SceneToken) -> Option<PathBuf> {
+        match token {
+            SceneToken::Number(id) => self.source_ids.get(id).cloned(),
+            SceneToken::Text(text) => {
+                // Sub-asset labels, e.g. "model.glb#Scene0", refer to the file itself
+                let text = text.split('#').next()?;
+                if let Ok(guid) = Uuid::parse_str(text) {
+                    return self.guids.path(&guid).map(Path::to_path_buf);
+                }
+                let path = Path::new(text);
+                [path.to_path_buf(), self.root.path.join(path)]
+                    .into_iter()
+                    .find(|candidate| !text.is_empty() && self.root.find_asset(candidate).is_some())
+            }
+        }
+    }
+}
+
+struct ParsedScene {
+    modified: Option<SystemTime>,
+    tokens: Vec<SceneToken>,
+    dependencies: Vec<PathBuf>,
+}
+
+/// Resource with assets referenced by every scene in AssetDirectory.
+/// Scenes are parsed again only when their modification time changes
+#[derive(Default)]
+pub struct AssetDependencyGraph {
+    scenes: HashMap<PathBuf, ParsedScene>,
+    built: bool,
+}
+
+impl AssetDependencyGraph {
+    /// Parse scenes changed since the last refresh and resolve references of all scenes again,
+    /// as referenced assets may have been added, moved or removed
+    pub fn refresh(&mut self, root: &AssetDirectory, guids: &AssetGuidIndex) {
+        let scenes: Vec<PathBuf> = root
+            .iter_assets()
+            .filter(|asset| matches!(asset, AssetType::Scene(_)))
+            .map(AssetType::get_path)
+            .collect();
+        self.scenes.retain(|path, _| scenes.contains(path));
+        for scene in scenes {
+            let modified = std::fs::metadata(&scene)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let up_to_date = self
+                .scenes
+                .get(&scene)
+                .is_some_and(|parsed| modified.is_some() && parsed.modified == modified);
+            if up_to_date {
+                continue;
+            }
+            let tokens = match std::fs::read_to_string(&scene) {
+                Ok(content) => tokenize(&content),
+                Err(e) => {
+                    warn!("Failed to read scene {:?}: {}", scene, e);
+                    Vec::new()
+                }
+            };
+            let parsed = ParsedScene {
+                modified,
+                tokens,
+                dependencies: Vec::new(),
+            };
+            self.scenes.insert(scene, parsed);
+        }
+
+        let resolver = AssetPathResolver::new(root, guids);
+        for (path, scene) in self.scenes.iter_mut() {
+            let mut dependencies: Vec<PathBuf> = scene
+                .tokens
+                .iter()
+                .filter_map(|token| resolver.resolve(token))
+                .filter(|dependency| dependency != path)
+                .collect();
+            dependencies.sort();
+            dependencies.dedup();
+            scene.dependencies = dependencies;
+        }
+        self.built = true;
+    }
+
+    /// Assets referenced by the scene
+    pub fn dependencies(&self, scene: &Path) -> &[PathBuf] {
+        self.scenes
+            .get(scene)
+            .map_or(&[], |parsed| parsed.dependencies.as_slice())
+    }
+
+    /// Scenes referencing the asset, or any asset in the directory. Scenes located inside
+    /// the directory itself are not reported
+    pub fn references(&self, path: &Path) -> Vec<&Path> {
+        let mut references: Vec<&Path> = self
+            .scenes
+            .iter()
+            .filter(|(scene, _)| !scene.starts_with(path))
+            .filter(|(_, parsed)| {
+                parsed
+                    .dependencies
+                    .iter()
+                    .any(|dependency| dependency.starts_with(path))
+            })
+            .map(|(scene, _)| scene.as_path())
+            .collect();
+        references.sort();
+        references
+    }
+
+    /// Warning to be confirmed before deleting or moving the assets.
+    /// None if none of them is referenced
+    pub fn reference_warning(&self, paths: &[PathBuf]) -> Option<String> {
+        let mut references: Vec<&Path> = paths
+            .iter()
+            .flat_map(|path| self.references(path))
+            .filter(|scene| !paths.iter().any(|path| scene.starts_with(path)))
+            .collect();
+        references.sort();
+        references.dedup();
+        if references.is_empty() {
+            return None;
+        }
+        let scenes: Vec<String> = references
+            .iter()
+            .map(|scene| scene.display().to_string())
+            .collect();
+        Some(format!(
+            "Still referenced by {} scene(s):\n{}",
+            scenes.len(),
+            scenes.join("\n")
+        ))
+    }
+}
+
+/// Build the graph once assets are loaded and refresh it whenever AssetDirectory or content
+/// of an asset changes. Referenced assets removed or moved outside the editor are reported,
+/// as references to them are broken now. Editor commands warn about them before they happen
+#[allow(clippy::too_many_arguments)]
+pub fn update_dependency_graph_system(
+    root: Res<AssetDirectory>,
+    guids: Res<AssetGuidIndex>,
+    time: Res<Time>,
+    notifications: Option<ResMut<EditorNotifications>>,
+    mut graph: ResMut<AssetDependencyGraph>,
+    mut added_reader: EventReader<AssetAddedEvent>,
+    mut removed_reader: EventReader<AssetRemovedEvent>,
+    mut moved_reader: EventReader<AssetMovedEvent>,
+    mut modified_reader: EventReader<AssetModifiedEvent>,
+) {
+    let mut changed = added_reader.iter().count() + modified_reader.iter().count();
+    let mut gone: Vec<PathBuf> = Vec::new();
+    let removed = removed_reader
+        .iter()
+        .map(|event| (&event.path, event.from_command));
+    let moved = moved_reader
+        .iter()
+        .map(|event| (&event.from, event.from_command));
+    for (path, from_command) in removed.chain(moved) {
+        match from_command {
+            true => changed += 1,
+            false => gone.push(path.clone()),
+        }
+    }
+    if changed == 0 && gone.is_empty() && graph.built {
+        return;
+    }
+
+    if let Some(warning) = graph.reference_warning(&gone) {
+        let text = format!("Moved or removed assets were in use. {}", warning);
+        match notifications {
+            Some(mut notifications) => notifications.push(text, true, &time),
+            None => warn!("{}", text),
+        }
+    }
+    graph.refresh(&root, &guids);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::assets::asset_loader::{GenericAssetDescriptor, SceneAssetDescriptor};
+    use crate::editor::assets::ignore_rules::AssetIgnoreRules;
+
+    fn create_asset(path: &Path) -> Option<AssetType> {
+        let name = path.file_name()?.to_string_lossy();
+        match name.ends_with(".scn.ron") {
+            true => Some(AssetType::Scene(SceneAssetDescriptor {
+                name: name.to_string().into(),
+                path: path.to_path_buf(),
+            })),
+            false => Some(AssetType::Other(GenericAssetDescriptor::new(path, "Other"))),
+        }
+    }
+
+    #[test]
+    fn scenes_reference_assets_by_path_guid_and_handle() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root_path = temp_dir.path().to_path_buf();
+        for file in ["textures/grass.png", "models/tree.glb", "audio/wind.ogg"] {
+            std::fs::create_dir_all(root_path.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root_path.join(file), "").unwrap();
+        }
+        let tree_guid = Uuid::new_v4();
+        let wind_id = source_path_id(Path::new("audio/wind.ogg")).unwrap();
+        std::fs::create_dir_all(root_path.join("scenes")).unwrap();
+        let level = root_path.join("scenes/level.scn.ron");
+        let content = format!(
+            r#"(entities: [(components: [
+                {{ "texture": "textures/grass.png" }},
+                {{ "model": "{}#Scene0" }},
+                {{ "sound": AssetPathId(({}, 1)) }},
+                {{ "name": "textures" }},
+            ])])"#,
+            tree_guid, wind_id
+        );
+        std::fs::write(&level, content).unwrap();
+
+        let mut root = AssetDirectory::new(root_path.clone());
+        root.insert_recursive(
+            &root_path,
+            &mut create_asset,
+            &mut AssetIgnoreRules::new(&root_path),
+        );
+        let mut guids = AssetGuidIndex::default();
+        guids.insert(tree_guid, root_path.join("models/tree.glb"));
+        let mut graph = AssetDependencyGraph::default();
+        graph.refresh(&root, &guids);
+
+        assert_eq!(
+            graph.dependencies(&level),
+            &[
+                root_path.join("audio/wind.ogg"),
+                root_path.join("models/tree.glb"),
+                root_path.join("textures/grass.png"),
+            ]
+        );
+        assert_eq!(
+            graph.references(&root_path.join("textures/grass.png")),
+            vec![level.as_path()]
+        );
+        assert!(graph.references(&root_path.join("scenes")).is_empty());
+        assert!(graph
+            .reference_warning(&[root_path.join("models")])
+            .is_some());
+        assert!(graph
+            .reference_warning(&[root_path.join("models"), root_path.join("scenes")])
+            .is_none());
+    }
+
+    #[test]
+    fn only_external_changes_are_reported() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root_path = temp_dir.path().to_path_buf();
+        let grass = root_path.join("grass.png");
+        let level = root_path.join("level.scn.ron");
+        std::fs::write(&grass, "").unwrap();
+        std::fs::write(&level, r#"{ "texture": "grass.png" }"#).unwrap();
+        let mut root = AssetDirectory::new(root_path.clone());
+        root.insert_recursive(
+            &root_path,
+            &mut create_asset,
+            &mut AssetIgnoreRules::new(&root_path),
+        );
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(root)
+            .init_resource::<AssetGuidIndex>()
+            .init_resource::<AssetDependencyGraph>()
+            .init_resource::<EditorNotifications>()
+            .add_event::<AssetAddedEvent>()
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
+            .add_event::<AssetModifiedEvent>()
+            .add_system(update_dependency_graph_system);
+        app.update();
+
+        for from_command in [true, false] {
+            app.world.send_event(AssetRemovedEvent {
+                path: grass.clone(),
+                from_command,
+            });
+            app.update();
+        }
+        assert_eq!(app.world.resource::<EditorNotifications>().items.len(), 1);
+
+        std::fs::write(&level, "()").unwrap();
+        app.world.send_event(AssetModifiedEvent { path: level });
+        app.update();
+        let graph = app.world.resource::<AssetDependencyGraph>();
+        assert!(graph.references(&grass).is_empty());
+    }
+}
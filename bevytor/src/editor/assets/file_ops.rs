@@ -0,0 +1,780 @@
+use crate::editor::assets::asset_loader::{AssetDirectory, AssetType, GenericAssetDescriptor};
+use crate::editor::assets::asset_types::{AssetLoadContext, AssetTypeRegistry};
+use crate::editor::assets::ignore_rules::AssetIgnoreRules;
+use crate::editor::assets::meta::{meta_path, rename_with_sidecar, AssetGuidIndex, META_EXTENSION};
+use crate::editor::assets::watcher::{
+    AssetAddedEvent, AssetChange, AssetMovedEvent, AssetRemovedEvent, WatchedChange,
+};
+use crate::editor::commands::dispatch::{ApplyCommand, CommandBatch, CommandWriter};
+use crate::editor::commands::persistence::{CommandHistoryState, SaveCommandHistoryEvent};
+use crate::editor::commands::{Command, CommandAny, CommandError, CommandQueue, CompoundCommand};
+use crate::editor::editor_directory;
+use crate::editor::ui::asset_browser::{sync_selected_directory, SelectedDirectory};
+use bevy::ecs::event::Events;
+use bevy::ecs::system::{SystemParam, SystemState};
+use bevy::prelude::*;
+use bevy::utils::Uuid;
+use bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the editor directory where deleted assets are kept
+pub const TRASH_DIRECTORY: &str = "trash";
+
+/// Default name of directories created by the asset browser
+pub const NEW_FOLDER_NAME: &str = "New Folder";
+
+/// Resource with location of deleted assets. Every deleted entry gets its own subdirectory,
+/// so entries with the same name don't collide
+pub struct AssetTrash {
+    pub path: PathBuf,
+}
+
+impl Default for AssetTrash {
+    fn default() -> Self {
+        Self {
+            path: editor_directory().join(TRASH_DIRECTORY),
+        }
+    }
+}
+
+impl AssetTrash {
+    /// Unique location for the deleted entry
+    pub fn entry(&self, path: &Path) -> PathBuf {
+        self.path
+            .join(Uuid::new_v4().to_string())
+            .join(path.file_name().unwrap_or_default())
+    }
+
+    /// Remove trash entries no longer reachable by undoing commands of the queue, e.g. entries
+    /// of commands evicted from the history
+    pub fn sweep(&self, queue: &CommandQueue) {
+        let mut referenced = Vec::new();
+        for item in queue.items.iter() {
+            collect_trash_entries(item.as_ref(), &mut referenced);
+        }
+        let entries = match std::fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if referenced.iter().any(|trash| trash.starts_with(&path)) {
+                continue;
+            }
+            let removed = match path.is_dir() {
+                true => std::fs::remove_dir_all(&path),
+                false => std::fs::remove_file(&path),
+            };
+            if let Err(e) = removed {
+                warn!("Failed to remove trash entry {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Trash entries of all the DeleteAssetCommands within the command
+fn collect_trash_entries(command: &dyn CommandAny, entries: &mut Vec<PathBuf>) {
+    let command = command.as_any();
+    if let Some(delete) = command.downcast_ref::<DeleteAssetCommand>() {
+        entries.push(delete.trash.clone());
+    } else if let Some(batch) = command.downcast_ref::<CommandBatch<DeleteAssetCommand>>() {
+        entries.extend(batch.commands.iter().map(|delete| delete.trash.clone()));
+    } else if let Some(compound) = command.downcast_ref::<CompoundCommand>() {
+        for child in compound.children.iter() {
+            collect_trash_entries(child.as_ref(), entries);
+        }
+    }
+}
+
+/// Sweep the trash once the previous session history is loaded. Unless the history was
+/// restored, the trash is left alone, as its entries may still be referenced
+pub fn sweep_asset_trash_system(
+    history_state: Res<CommandHistoryState>,
+    trash: Res<AssetTrash>,
+    queue: Res<CommandQueue>,
+) {
+    if history_state.restored {
+        trash.sweep(&queue);
+    }
+}
+
+/// File operation requested in the asset browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperation {
+    /// Rename or move asset or directory
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Delete(PathBuf),
+    Duplicate(PathBuf),
+    /// Create directory inside given one
+    NewFolder(PathBuf),
+}
+
+impl FileOperation {
+    /// Paths whose references get broken by the operation
+    pub fn affected_paths(&self) -> &[PathBuf] {
+        match self {
+            FileOperation::Move { from, .. } => std::slice::from_ref(from),
+            FileOperation::Delete(path) => std::slice::from_ref(path),
+            FileOperation::Duplicate(_) | FileOperation::NewFolder(_) => &[],
+        }
+    }
+}
+
+/// Writers of the file operation commands
+#[derive(SystemParam)]
+pub struct FileOperationWriters<'w, 's> {
+    trash: Res<'w, AssetTrash>,
+    writer: CommandWriter<'w, 's>,
+}
+
+impl<'w, 's> FileOperationWriters<'w, 's> {
+    /// Send commands performing the operations. Operations of the same kind are batched,
+    /// so e.g. deleting several assets is undone in one step
+    #[track_caller]
+    pub fn send_all(&mut self, operations: Vec<FileOperation>) {
+        if operations.len() < 2 {
+            for operation in operations {
+                self.send(operation);
+            }
+            return;
+        }
+
+        let mut moves = Vec::new();
+        let mut deletes = Vec::new();
+        let mut duplicates = Vec::new();
+        for operation in operations {
+            match operation {
+                FileOperation::Move { from, to } => moves.push(MoveAssetCommand { from, to }),
+                FileOperation::Delete(path) => deletes.push(DeleteAssetCommand {
+                    trash: self.trash.entry(&path),
+                    path,
+                }),
+                FileOperation::Duplicate(source) => {
+                    let copy = unique_path(&source);
+                    duplicates.push(DuplicateAssetCommand { source, copy })
+                }
+                operation @ FileOperation::NewFolder(_) => self.send(operation),
+            }
+        }
+        if !moves.is_empty() {
+            self.writer.send(CommandBatch { commands: moves });
+        }
+        if !deletes.is_empty() {
+            self.writer.send(CommandBatch { commands: deletes });
+        }
+        if !duplicates.is_empty() {
+            self.writer.send(CommandBatch {
+                commands: duplicates,
+            });
+        }
+    }
+
+    /// Send command performing the operation
+    #[track_caller]
+    pub fn send(&mut self, operation: FileOperation) {
+        match operation {
+            FileOperation::Move { from, to } => self.writer.send(MoveAssetCommand { from, to }),
+            FileOperation::Delete(path) => {
+                let trash = self.trash.entry(&path);
+                self.writer.send(DeleteAssetCommand { path, trash })
+            }
+            FileOperation::Duplicate(source) => {
+                let copy = unique_path(&source);
+                self.writer.send(DuplicateAssetCommand { source, copy })
+            }
+            FileOperation::NewFolder(parent) => {
+                let path = unique_path(&parent.join(NEW_FOLDER_NAME));
+                self.writer.send(CreateDirectoryCommand { path })
+            }
+        }
+    }
+}
+
+/// Returns the path itself if it is free, otherwise appends the lowest free number to the name,
+/// e.g. "grass 2.png". Everything after the first dot is treated as the extension, so
+/// "level.scn.ron" becomes "level 2.scn.ron"
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let (stem, extension) = match name.get(1..).and_then(|rest| rest.find('.')) {
+        Some(index) => name.split_at(index + 1),
+        None => (name.as_str(), ""),
+    };
+    (2..)
+        .map(|number| path.with_file_name(format!("{} {}{}", stem, number, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// Copy file together with its sidecar, or directory with all of its content.
+/// Copied sidecars get fresh GUIDs once the copies are registered
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::copy(from, to)?;
+    let is_sidecar = from
+        .extension()
+        .is_some_and(|extension| extension == META_EXTENSION);
+    if !is_sidecar && meta_path(from).exists() {
+        std::fs::copy(meta_path(from), meta_path(to))?;
+    }
+    Ok(())
+}
+
+/// Remove file together with its sidecar, or directory with all of its content
+fn remove_recursive(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        return std::fs::remove_dir_all(path);
+    }
+    std::fs::remove_file(path)?;
+    match std::fs::remove_file(meta_path(path)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn already_exists_error(path: &Path) -> CommandError {
+    CommandError::Other(format!("{:?} already exists", path))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Resources required for inserting changed paths into AssetDirectory
+type FileChangeParams = (
+    Option<Res<'static, AssetServer>>,
+    Option<Res<'static, AssetTypeRegistry>>,
+    Option<ResMut<'static, EguiContext>>,
+    Option<ResMut<'static, Assets<Image>>>,
+    ResMut<'static, AssetGuidIndex>,
+    ResMut<'static, AssetDirectory>,
+    ResMut<'static, AssetIgnoreRules>,
+);
+
+/// Apply changes made by the editor to AssetDirectory right away, instead of waiting for the
+/// watcher to notice them. Changes are reported through asset events, as the watcher does.
+/// Without the asset loading resources (e.g. headless app) assets are listed as generic files
+pub fn apply_file_changes(world: &mut World, changes: Vec<WatchedChange>) {
+    world.get_resource_or_insert_with(AssetGuidIndex::default);
+    if !world.contains_resource::<AssetIgnoreRules>() {
+        let root = world.resource::<AssetDirectory>().path.clone();
+        world.insert_resource(AssetIgnoreRules::new(&root));
+    }
+
+    let mut state: SystemState<FileChangeParams> = SystemState::new(world);
+    let (asset_server, asset_types, mut egui_context, mut images, mut guids, mut root, mut rules) =
+        state.get_mut(world);
+
+    let mut asset_changes = Vec::new();
+    {
+        let mut context = match (
+            asset_server.as_deref(),
+            egui_context.as_deref_mut(),
+            images.as_deref_mut(),
+        ) {
+            (Some(asset_server), Some(egui_context), Some(images)) => Some(AssetLoadContext {
+                asset_server,
+                egui_context,
+                images,
+                guids: &mut guids,
+            }),
+            _ => None,
+        };
+        let mut create_asset = |path: &Path| match (asset_types.as_deref(), context.as_mut()) {
+            (Some(asset_types), Some(context)) => asset_types.create(path, context),
+            _ => (path.extension() != Some(META_EXTENSION.as_ref()))
+                .then(|| AssetType::Other(GenericAssetDescriptor::new(path, "Other"))),
+        };
+        for change in changes {
+            asset_changes.extend(root.apply_watched_change(change, &mut create_asset, &mut rules));
+        }
+    }
+    for asset_change in asset_changes.iter() {
+        guids.apply_change(asset_change);
+    }
+
+    if world.contains_resource::<SelectedDirectory>() {
+        world.resource_scope(|world, mut selected: Mut<SelectedDirectory>| {
+            let root = world.resource::<AssetDirectory>();
+            sync_selected_directory(root, &mut selected, &asset_changes);
+        });
+    }
+    for asset_change in asset_changes {
+        send_asset_change(world, asset_change);
+    }
+}
+
+/// Send asset event describing the change, if the event is registered
+fn send_asset_change(world: &mut World, change: AssetChange) {
+    match change {
+        AssetChange::Added(path) => {
+            if let Some(mut events) = world.get_resource_mut::<Events<AssetAddedEvent>>() {
+                events.send(AssetAddedEvent { path });
+            }
+        }
+        AssetChange::Removed(path) => {
+            if let Some(mut events) = world.get_resource_mut::<Events<AssetRemovedEvent>>() {
+                events.send(AssetRemovedEvent {
+                    path,
+                    from_command: true,
+                });
+            }
+        }
+        AssetChange::Moved { from, to } => {
+            if let Some(mut events) = world.get_resource_mut::<Events<AssetMovedEvent>>() {
+                events.send(AssetMovedEvent {
+                    from,
+                    to,
+                    from_command: true,
+                });
+            }
+        }
+    }
+}
+
+/// Rename or move asset or directory. Sidecar is moved together with the asset,
+/// so the asset keeps its GUID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveAssetCommand {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl MoveAssetCommand {
+    fn move_path(world: &mut World, from: &Path, to: &Path) -> Result<(), CommandError> {
+        if to.exists() {
+            return Err(already_exists_error(to));
+        }
+        rename_with_sidecar(from, to)?;
+        let change = WatchedChange::Moved {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        };
+        apply_file_changes(world, vec![change]);
+        Ok(())
+    }
+}
+
+impl Command for MoveAssetCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<MoveAssetCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.from.as_os_str().len() + self.to.as_os_str().len()
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![
+            TypeId::of::<AssetDirectory>(),
+            TypeId::of::<SelectedDirectory>(),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        match self.from.parent() == self.to.parent() {
+            true => format!(
+                "Rename {} to {}",
+                file_name(&self.from),
+                file_name(&self.to)
+            ),
+            false => match self.to.parent() {
+                Some(parent) => format!("Move {} to {}", file_name(&self.from), file_name(parent)),
+                None => format!("Move {}", file_name(&self.from)),
+            },
+        }
+    }
+}
+
+impl ApplyCommand for MoveAssetCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        Self::move_path(world, &self.from, &self.to)
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        Self::move_path(world, &self.to, &self.from)
+    }
+}
+
+/// Move asset or directory to the trash, so it can be restored by undo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAssetCommand {
+    pub path: PathBuf,
+    /// Location of the entry in the trash
+    pub trash: PathBuf,
+}
+
+impl Command for DeleteAssetCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<DeleteAssetCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.path.as_os_str().len() + self.trash.as_os_str().len()
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![
+            TypeId::of::<AssetDirectory>(),
+            TypeId::of::<SelectedDirectory>(),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        format!("Delete {}", file_name(&self.path))
+    }
+}
+
+impl ApplyCommand for DeleteAssetCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        if self.trash.exists() {
+            return Err(already_exists_error(&self.trash));
+        }
+        if let Some(parent) = self.trash.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        rename_with_sidecar(&self.path, &self.trash)?;
+        apply_file_changes(world, vec![WatchedChange::Touched(self.path.clone())]);
+        // Trash entry is swept at startup unless the history referencing it is saved
+        world.send_event(SaveCommandHistoryEvent);
+        Ok(())
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        if self.path.exists() {
+            return Err(already_exists_error(&self.path));
+        }
+        rename_with_sidecar(&self.trash, &self.path)?;
+        if let Some(parent) = self.trash.parent() {
+            // Directory is shared with nothing else, leftovers only mean it wasn't empty
+            let _ = std::fs::remove_dir(parent);
+        }
+        apply_file_changes(world, vec![WatchedChange::Touched(self.path.clone())]);
+        Ok(())
+    }
+}
+
+/// Copy asset or directory next to the original
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateAssetCommand {
+    pub source: PathBuf,
+    pub copy: PathBuf,
+}
+
+impl Command for DuplicateAssetCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<DuplicateAssetCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.source.as_os_str().len() + self.copy.as_os_str().len()
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![
+            TypeId::of::<AssetDirectory>(),
+            TypeId::of::<SelectedDirectory>(),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        format!("Duplicate {}", file_name(&self.source))
+    }
+}
+
+impl ApplyCommand for DuplicateAssetCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        if self.copy.exists() {
+            return Err(already_exists_error(&self.copy));
+        }
+        if let Err(e) = copy_recursive(&self.source, &self.copy) {
+            // Don't leave partial copy behind
+            let _ = remove_recursive(&self.copy);
+            return Err(e.into());
+        }
+        apply_file_changes(world, vec![WatchedChange::Touched(self.copy.clone())]);
+        Ok(())
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        remove_recursive(&self.copy)?;
+        apply_file_changes(world, vec![WatchedChange::Touched(self.copy.clone())]);
+        Ok(())
+    }
+}
+
+/// Create empty directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDirectoryCommand {
+    pub path: PathBuf,
+}
+
+impl Command for CreateDirectoryCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<CreateDirectoryCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.path.as_os_str().len()
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![
+            TypeId::of::<AssetDirectory>(),
+            TypeId::of::<SelectedDirectory>(),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        format!("Create folder {}", file_name(&self.path))
+    }
+}
+
+impl ApplyCommand for CreateDirectoryCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        std::fs::create_dir(&self.path)?;
+        apply_file_changes(world, vec![WatchedChange::Touched(self.path.clone())]);
+        Ok(())
+    }
+
+    /// Fails if anything was put into the directory in the meantime, so nothing gets lost
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        std::fs::remove_dir(&self.path)?;
+        apply_file_changes(world, vec![WatchedChange::Touched(self.path.clone())]);
+        Ok(())
+    }
+}
+
+/// Name validation used by the rename dialog. Returns the new path of the entry
+pub fn renamed_path(path: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err("Name can't be empty".into());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("Name can't contain path separators".into());
+    }
+    let renamed = path.with_file_name(OsString::from(name));
+    if renamed != path && renamed.exists() {
+        return Err(format!("{} already exists", name));
+    }
+    Ok(renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::commands::dispatch::AddEditorCommand;
+    use crate::editor::commands::persistence::{
+        load_command_history_system, CommandHistoryFile, RegisterEditorCommand,
+        COMMAND_HISTORY_FILE,
+    };
+    use crate::editor::testing::CommandTestApp;
+
+    fn file_ops_app(directories: &[&str]) -> CommandTestApp {
+        let mut app = CommandTestApp::new().with_asset_directories(directories);
+        let trash = app.path(".trash");
+        app.app
+            .insert_resource(AssetTrash { path: trash })
+            .add_editor_command::<MoveAssetCommand>()
+            .add_editor_command::<DeleteAssetCommand>()
+            .add_editor_command::<DuplicateAssetCommand>()
+            .add_editor_command::<CreateDirectoryCommand>();
+        app
+    }
+
+    fn root(app: &CommandTestApp) -> &AssetDirectory {
+        app.resource::<AssetDirectory>()
+    }
+
+    #[test]
+    fn renamed_and_deleted_assets_are_restored_by_undo() {
+        let mut app = file_ops_app(&["textures"]);
+        let grass = app.path("textures/grass.png");
+        std::fs::write(&grass, "grass").unwrap();
+        std::fs::write(meta_path(&grass), "").unwrap();
+        app.app
+            .world
+            .resource_mut::<AssetDirectory>()
+            .insert_path(&grass, &mut |path| {
+                Some(AssetType::Other(GenericAssetDescriptor::new(path, "Other")))
+            });
+
+        let moss = app.path("textures/moss.png");
+        app.send(MoveAssetCommand {
+            from: grass.clone(),
+            to: moss.clone(),
+        });
+        assert!(moss.exists() && meta_path(&moss).exists());
+        assert!(root(&app).contains(&moss));
+        assert!(!root(&app).contains(&grass));
+
+        let trash = app.resource::<AssetTrash>().entry(&moss);
+        app.send(DeleteAssetCommand {
+            path: moss.clone(),
+            trash: trash.clone(),
+        });
+        assert!(!moss.exists() && trash.exists());
+        assert!(!root(&app).contains(&moss));
+
+        app.undo();
+        assert!(moss.exists() && meta_path(&moss).exists());
+        assert!(!trash.parent().unwrap().exists());
+        assert!(root(&app).contains(&moss));
+        app.undo();
+        assert!(grass.exists());
+        assert!(root(&app).contains(&grass));
+        assert!(app.drain_failures().is_empty());
+    }
+
+    #[test]
+    fn duplicated_and_created_directories_are_removed_by_undo() {
+        let mut app = file_ops_app(&["models"]);
+        std::fs::write(app.path("models/tree.glb"), "").unwrap();
+        let new_folder = unique_path(&app.path(NEW_FOLDER_NAME));
+        app.send(CreateDirectoryCommand {
+            path: new_folder.clone(),
+        });
+        assert!(new_folder.is_dir());
+        assert_eq!(
+            app.resource::<SelectedDirectory>()
+                .directory()
+                .children_directories
+                .len(),
+            2
+        );
+
+        let copy = unique_path(&app.path("models"));
+        assert_eq!(copy, app.path("models 2"));
+        app.send(DuplicateAssetCommand {
+            source: app.path("models"),
+            copy: copy.clone(),
+        });
+        assert!(copy.join("tree.glb").exists());
+        assert!(root(&app).contains(&copy.join("tree.glb")));
+
+        app.undo();
+        app.undo();
+        assert!(!copy.exists() && !new_folder.exists());
+        assert!(!root(&app).contains(&copy));
+        assert_eq!(
+            app.resource::<SelectedDirectory>()
+                .directory()
+                .children_directories
+                .len(),
+            1
+        );
+        app.redo();
+        assert!(new_folder.is_dir());
+    }
+
+    #[test]
+    fn trash_entries_without_command_are_swept() {
+        let mut app = file_ops_app(&["textures"]);
+        let textures = app.path("textures");
+        let trash = app.resource::<AssetTrash>().entry(&textures);
+        app.send(DeleteAssetCommand {
+            path: textures.clone(),
+            trash: trash.clone(),
+        });
+        let orphan = app.path(".trash/orphan/grass.png");
+        std::fs::create_dir_all(&orphan).unwrap();
+
+        app.resource::<AssetTrash>().sweep(app.queue());
+        assert!(trash.exists());
+        assert!(!orphan.parent().unwrap().exists());
+        app.undo();
+        assert!(textures.is_dir());
+    }
+
+    #[test]
+    fn trash_is_kept_when_history_is_not_restored() {
+        let mut app = file_ops_app(&["textures"]);
+        let history = app.path(COMMAND_HISTORY_FILE);
+        std::fs::write(&history, "corrupt").unwrap();
+        app.app
+            .insert_resource(CommandHistoryFile {
+                path: history.clone(),
+            })
+            .add_startup_system(sweep_asset_trash_system.after(load_command_history_system));
+        let entry = app.path(".trash/entry/grass.png");
+        std::fs::create_dir_all(&entry).unwrap();
+
+        app.update();
+        assert!(entry.exists());
+        assert!(!app.resource::<CommandHistoryState>().restored);
+    }
+
+    #[test]
+    fn history_is_saved_right_after_delete() {
+        let mut app = file_ops_app(&["textures"]);
+        let history = app.path(COMMAND_HISTORY_FILE);
+        app.app
+            .insert_resource(CommandHistoryFile {
+                path: history.clone(),
+            })
+            .register_editor_command::<DeleteAssetCommand>("bevytor::DeleteAssetCommand");
+        let textures = app.path("textures");
+        let trash = app.resource::<AssetTrash>().entry(&textures);
+        app.send(DeleteAssetCommand {
+            path: textures,
+            trash,
+        });
+        assert!(history.exists());
+    }
+
+    #[test]
+    fn unique_path_keeps_multi_part_extensions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let level = temp_dir.path().join("level.scn.ron");
+        assert_eq!(unique_path(&level), level);
+        std::fs::write(&level, "").unwrap();
+        std::fs::write(temp_dir.path().join("level 2.scn.ron"), "").unwrap();
+        assert_eq!(unique_path(&level), temp_dir.path().join("level 3.scn.ron"));
+        assert!(renamed_path(&level, "level 2.scn.ron").is_err());
+        assert!(renamed_path(&level, "a/b").is_err());
+        assert_eq!(
+            renamed_path(&level, " arena.scn.ron "),
+            Ok(temp_dir.path().join("arena.scn.ron"))
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Size of the font thumbnail, in pixels
+pub const FONT_THUMBNAIL_SIZE: u32 = 128;
+/// Text rendered in the font thumbnail
+const SAMPLE_TEXT: &str = "Aa";
+
+/// Render sample text with the font into a square RGBA image
+pub fn render_font_sample(font_data: Vec<u8>) -> Result<Image, String> {
+    let font = FontVec::try_from_vec(font_data).map_err(|e| e.to_string())?;
+    let scaled_font = font.as_scaled(PxScale::from(FONT_THUMBNAIL_SIZE as f32 * 0.6));
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for character in SAMPLE_TEXT.chars() {
+        let glyph_id = scaled_font.glyph_id(character);
+        if let Some(previous) = previous {
+            caret += scaled_font.kern(previous, glyph_id);
+        }
+        glyphs.push(glyph_id.with_scale_and_position(scaled_font.scale(), point(caret, 0.0)));
+        caret += scaled_font.h_advance(glyph_id);
+        previous = Some(glyph_id);
+    }
+
+    // Center the text, baseline is placed so ascent and descent are evenly distributed
+    let size = FONT_THUMBNAIL_SIZE as f32;
+    let offset_x = (size - caret) / 2.0;
+    let offset_y = (size + scaled_font.ascent() + scaled_font.descent()) / 2.0;
+    let mut data = vec![0u8; (FONT_THUMBNAIL_SIZE * FONT_THUMBNAIL_SIZE * 4) as usize];
+    for glyph in glyphs {
+        let outlined = match font.outline_glyph(glyph) {
+            Some(outlined) => outlined,
+            None => continue,
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|x, y, coverage| {
+            let x = (x as f32 + bounds.min.x + offset_x) as i64;
+            let y = (y as f32 + bounds.min.y + offset_y) as i64;
+            if x < 0 || y < 0 || x >= size as i64 || y >= size as i64 {
+                return;
+            }
+            let index = ((y as u32 * FONT_THUMBNAIL_SIZE + x as u32) * 4) as usize;
+            let alpha = ((coverage.clamp(0.0, 1.0) * 255.0) as u8).max(data[index + 3]);
+            data[index..index + 4].copy_from_slice(&[255, 255, 255, alpha]);
+        });
+    }
+
+    Ok(Image::new(
+        Extent3d {
+            width: FONT_THUMBNAIL_SIZE,
+            height: FONT_THUMBNAIL_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_egui::egui::FontDefinitions;
+
+    #[test]
+    fn sample_text_is_rendered() {
+        let definitions = FontDefinitions::default();
+        let font_data = definitions.font_data.values().next().unwrap();
+        let image = render_font_sample(font_data.font.to_vec()).unwrap();
+        assert_eq!(image.size(), Vec2::splat(FONT_THUMBNAIL_SIZE as f32));
+        assert!(image.data.chunks(4).any(|pixel| pixel[3] > 0));
+
+        assert!(render_font_sample(b"not a font".to_vec()).is_err());
+    }
+}
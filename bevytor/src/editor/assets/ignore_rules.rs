@@ -0,0 +1,162 @@
+use bevy::log::warn;
+use bevy::utils::HashMap;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+/// Name of the file with ignore rules, uses gitignore syntax. Rules apply to the directory
+/// containing the file and all of its subdirectories
+pub const IGNORE_FILE: &str = ".bevytorignore";
+
+/// Resource with ignore rules of the asset directory. Ignored files and directories are never
+/// inserted into AssetDirectory. Hidden files and directories are always ignored
+pub struct AssetIgnoreRules {
+    root: PathBuf,
+    /// Rules of every directory containing the ignore file
+    rules: HashMap<PathBuf, Gitignore>,
+}
+
+impl AssetIgnoreRules {
+    /// Rules of the root directory only, rules of subdirectories are loaded while walking them
+    pub fn new(root: &Path) -> Self {
+        let mut rules = Self {
+            root: root.to_path_buf(),
+            rules: HashMap::default(),
+        };
+        rules.load(root);
+        rules
+    }
+
+    /// Load ignore file of the directory, replacing its previous rules
+    pub fn load(&mut self, directory: &Path) {
+        let file = directory.join(IGNORE_FILE);
+        if !file.is_file() {
+            self.rules.remove(directory);
+            return;
+        }
+        let mut builder = GitignoreBuilder::new(directory);
+        if let Some(e) = builder.add(&file) {
+            warn!("Invalid rules in {:?}: {}", file, e);
+        }
+        match builder.build() {
+            Ok(gitignore) => {
+                self.rules.insert(directory.to_path_buf(), gitignore);
+            }
+            Err(e) => warn!("Failed to load {:?}: {}", file, e),
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+        let hidden = relative
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden {
+            return true;
+        }
+
+        // Content of ignored directory can't be re-included, so every ancestor is checked
+        let mut current = self.root.clone();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            current.push(component);
+            let is_last = components.peek().is_none();
+            if self.matches(&current, !is_last || is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Rules of the closest directory matching the path take precedence
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        for directory in path.ancestors().skip(1) {
+            if let Some(gitignore) = self.rules.get(directory) {
+                match gitignore.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if directory == self.root {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Walk the directory, skipping ignored entries. Rules of visited directories are loaded
+    /// before their content is walked
+    pub fn walk<'a>(&'a mut self, path: &Path) -> impl Iterator<Item = DirEntry> + 'a {
+        WalkDir::new(path)
+            .into_iter()
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_dir();
+                if self.is_ignored(entry.path(), is_dir) {
+                    return false;
+                }
+                if is_dir {
+                    self.load(entry.path());
+                }
+                true
+            })
+            .filter_map(|e| e.ok())
+    }
+}
+
+/// Checks if the path is an ignore file, whose change affects the whole directory
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == IGNORE_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_rules_and_hidden_files_are_ignored() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let files = [
+            ".git/HEAD",
+            "cache/lightmap.png",
+            "textures/grass.png",
+            "textures/grass.png~",
+            "textures/keep.png~",
+            "textures/raw/grass.psd",
+            "textures/.DS_Store",
+        ];
+        for file in files {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        std::fs::write(root.join(IGNORE_FILE), "cache/\n*~\n").unwrap();
+        std::fs::write(root.join("textures").join(IGNORE_FILE), "raw\n!keep.png~\n").unwrap();
+
+        let mut rules = AssetIgnoreRules::new(root);
+        let mut walked: Vec<PathBuf> = rules
+            .walk(root)
+            .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        walked.sort();
+        assert_eq!(
+            walked,
+            vec![
+                PathBuf::new(),
+                PathBuf::from("textures"),
+                PathBuf::from("textures/grass.png"),
+                PathBuf::from("textures/keep.png~"),
+            ]
+        );
+
+        std::fs::remove_file(root.join("textures").join(IGNORE_FILE)).unwrap();
+        rules.load(&root.join("textures"));
+        assert!(rules.is_ignored(&root.join("textures/keep.png~"), false));
+        assert!(!rules.is_ignored(&root.join("textures/raw/grass.psd"), false));
+        assert!(rules.is_ignored(&root.join("cache/lightmap.png"), false));
+    }
+}
@@ -0,0 +1,462 @@
+use crate::editor::assets::asset_loader::AssetType;
+use crate::editor::assets::meta::{meta_path, AssetMeta};
+use bevy::asset::{AssetServerSettings, FileAssetIo, HandleId};
+use bevy::audio::AudioSink;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AddressMode, Extent3d, FilterMode, SamplerDescriptor, TextureDimension, TextureFormat,
+};
+use bevy::render::texture::ImageSampler;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// ImportSettingsPlugin applies import settings stored in asset sidecars whenever images and
+/// model scenes are loaded, audio is played with its settings through ImportedAudio.
+/// Used by both the editor and the game
+pub struct ImportSettingsPlugin;
+impl Plugin for ImportSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImportedAssets>()
+            .init_resource::<AudioPlaybackSettings>()
+            .add_event::<ImportSettingsChangedEvent>()
+            .add_system(reimport_changed_assets_system)
+            .add_system(apply_image_import_settings_system.after(reimport_changed_assets_system))
+            .add_system(apply_model_import_settings_system.after(reimport_changed_assets_system));
+    }
+}
+
+/// Settings used when the asset is imported. Assets without settings use bevy defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ImportSettings {
+    #[default]
+    None,
+    Image(ImageImportSettings),
+    Audio(AudioImportSettings),
+    Model(ModelImportSettings),
+}
+
+impl ImportSettings {
+    /// Default settings of the asset type, None if the type has no settings
+    pub fn default_for(asset: &AssetType) -> Self {
+        match asset {
+            AssetType::Image(_) => Self::Image(ImageImportSettings::default()),
+            AssetType::Audio(_) => Self::Audio(AudioImportSettings::default()),
+            AssetType::Model(_) => Self::Model(ModelImportSettings::default()),
+            _ => Self::None,
+        }
+    }
+
+    pub fn image(&self) -> Option<&ImageImportSettings> {
+        match self {
+            Self::Image(settings) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn audio(&self) -> Option<&AudioImportSettings> {
+        match self {
+            Self::Audio(settings) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn model(&self) -> Option<&ModelImportSettings> {
+        match self {
+            Self::Model(settings) => Some(settings),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageFilter {
+    Linear,
+    Nearest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageAddressMode {
+    ClampToEdge,
+    Repeat,
+    MirrorRepeat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageImportSettings {
+    pub filter: ImageFilter,
+    pub address_mode: ImageAddressMode,
+    /// Color data is stored in sRGB, disable for normal maps and other non-color data
+    pub srgb: bool,
+    /// Generate mipmaps on load. Only supported for 8 bit RGBA images
+    pub mipmaps: bool,
+}
+
+impl Default for ImageImportSettings {
+    fn default() -> Self {
+        Self {
+            filter: ImageFilter::Linear,
+            address_mode: ImageAddressMode::ClampToEdge,
+            srgb: true,
+            mipmaps: false,
+        }
+    }
+}
+
+impl ImageImportSettings {
+    pub fn apply(&self, image: &mut Image) {
+        let filter = match self.filter {
+            ImageFilter::Linear => FilterMode::Linear,
+            ImageFilter::Nearest => FilterMode::Nearest,
+        };
+        let address_mode = match self.address_mode {
+            ImageAddressMode::ClampToEdge => AddressMode::ClampToEdge,
+            ImageAddressMode::Repeat => AddressMode::Repeat,
+            ImageAddressMode::MirrorRepeat => AddressMode::MirrorRepeat,
+        };
+        image.sampler_descriptor = ImageSampler::Descriptor(SamplerDescriptor {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            ..Default::default()
+        });
+
+        let format = &mut image.texture_descriptor.format;
+        *format = match (*format, self.srgb) {
+            (TextureFormat::Rgba8Unorm, true) => TextureFormat::Rgba8UnormSrgb,
+            (TextureFormat::Rgba8UnormSrgb, false) => TextureFormat::Rgba8Unorm,
+            (TextureFormat::Bgra8Unorm, true) => TextureFormat::Bgra8UnormSrgb,
+            (TextureFormat::Bgra8UnormSrgb, false) => TextureFormat::Bgra8Unorm,
+            (format, _) => format,
+        };
+
+        if self.mipmaps {
+            generate_mipmaps(image);
+        }
+    }
+}
+
+/// Append downsampled mip levels to the image data. Images with other than 8 bit RGBA/BGRA
+/// format, or already containing mipmaps, are left untouched
+fn generate_mipmaps(image: &mut Image) {
+    let descriptor = &image.texture_descriptor;
+    let supported = matches!(
+        descriptor.format,
+        TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb
+    );
+    if !supported
+        || descriptor.mip_level_count != 1
+        || descriptor.dimension != TextureDimension::D2
+        || descriptor.size.depth_or_array_layers != 1
+    {
+        return;
+    }
+
+    let Extent3d {
+        mut width,
+        mut height,
+        ..
+    } = descriptor.size;
+    let mut level = image.data.clone();
+    let mut mip_level_count = 1;
+    while width > 1 || height > 1 {
+        let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+        let mut next = Vec::with_capacity((next_width * next_height * 4) as usize);
+        for y in 0..next_height {
+            for x in 0..next_width {
+                for channel in 0..4 {
+                    let sample = |x: u32, y: u32| {
+                        let (x, y) = (x.min(width - 1), y.min(height - 1));
+                        level[((y * width + x) * 4 + channel) as usize] as u32
+                    };
+                    let sum = sample(x * 2, y * 2)
+                        + sample(x * 2 + 1, y * 2)
+                        + sample(x * 2, y * 2 + 1)
+                        + sample(x * 2 + 1, y * 2 + 1);
+                    next.push((sum / 4) as u8);
+                }
+            }
+        }
+        image.data.extend_from_slice(&next);
+        level = next;
+        (width, height) = (next_width, next_height);
+        mip_level_count += 1;
+    }
+    image.texture_descriptor.mip_level_count = mip_level_count;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioImportSettings {
+    pub looped: bool,
+}
+
+impl AudioImportSettings {
+    /// Settings to be passed to `Audio::play_with_settings`, see ImportedAudio
+    pub fn playback_settings(&self) -> PlaybackSettings {
+        match self.looped {
+            true => PlaybackSettings::LOOP,
+            false => PlaybackSettings::ONCE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelImportSettings {
+    /// Uniform scale applied to root nodes of every model scene
+    pub scale: f32,
+}
+
+impl Default for ModelImportSettings {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+impl ModelImportSettings {
+    pub fn apply(&self, scene: &mut Scene) {
+        let mut roots = scene
+            .world
+            .query_filtered::<&mut Transform, Without<Parent>>();
+        for mut transform in roots.iter_mut(&mut scene.world) {
+            transform.scale *= self.scale;
+        }
+    }
+}
+
+/// Read import settings from the sidecar of the asset without creating it
+pub fn read_import_settings(asset_path: &Path) -> ImportSettings {
+    if !meta_path(asset_path).exists() {
+        return ImportSettings::None;
+    }
+    match AssetMeta::load_or_create(asset_path) {
+        Ok(meta) => meta.import_settings,
+        Err(e) => {
+            warn!("Failed to read meta file of {:?}: {}", asset_path, e);
+            ImportSettings::None
+        }
+    }
+}
+
+/// Store import settings in the sidecar of the asset, keeping its GUID
+pub fn save_import_settings(asset_path: &Path, settings: ImportSettings) -> Result<(), String> {
+    let mut meta = AssetMeta::load_or_create(asset_path)?;
+    meta.import_settings = settings;
+    meta.save(asset_path)
+}
+
+/// Sent after import settings of the asset were saved, so the asset gets imported again
+pub struct ImportSettingsChangedEvent {
+    pub path: PathBuf,
+}
+
+/// Resource tracking loaded assets with import settings already applied, as applying
+/// the settings modifies the asset again
+#[derive(Default)]
+pub struct ImportedAssets {
+    applied: HashMap<HandleId, PathBuf>,
+}
+
+/// Path of the file the asset was loaded from. Labels of sub-assets are dropped
+fn asset_file_path(
+    asset_server: &AssetServer,
+    settings: Option<&AssetServerSettings>,
+    id: HandleId,
+) -> Option<PathBuf> {
+    let asset_path = asset_server.get_handle_path(id)?;
+    let path = asset_path.path();
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    let asset_folder = settings.map_or("assets", |settings| settings.asset_folder.as_str());
+    Some(FileAssetIo::get_base_path().join(asset_folder).join(path))
+}
+
+/// Reload assets whose import settings changed, settings are applied once they are loaded
+pub fn reimport_changed_assets_system(
+    asset_server: Res<AssetServer>,
+    mut imported: ResMut<ImportedAssets>,
+    mut playback: ResMut<AudioPlaybackSettings>,
+    mut changed_reader: EventReader<ImportSettingsChangedEvent>,
+) {
+    for event in changed_reader.iter() {
+        imported.applied.retain(|_, path| path != &event.path);
+        playback
+            .by_handle
+            .retain(|_, (path, _)| path != &event.path);
+        asset_server.reload_asset(event.path.as_path());
+    }
+}
+
+/// Playback settings of audio assets, read from their sidecars on first use
+#[derive(Default)]
+pub struct AudioPlaybackSettings {
+    by_handle: HashMap<HandleId, (PathBuf, PlaybackSettings)>,
+}
+
+/// Plays audio assets with their import settings applied, e.g. looped.
+/// Audio is unavailable without an audio output device
+#[derive(SystemParam)]
+pub struct ImportedAudio<'w, 's> {
+    audio: Option<Res<'w, Audio>>,
+    asset_server: Res<'w, AssetServer>,
+    asset_settings: Option<Res<'w, AssetServerSettings>>,
+    playback: ResMut<'w, AudioPlaybackSettings>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ImportedAudio<'w, 's> {
+    pub fn is_available(&self) -> bool {
+        self.audio.is_some()
+    }
+
+    /// Playback settings from the import settings of the audio
+    pub fn settings(&mut self, source: &Handle<AudioSource>) -> PlaybackSettings {
+        let (asset_server, asset_settings) = (&self.asset_server, self.asset_settings.as_deref());
+        let path = match asset_file_path(asset_server, asset_settings, source.id) {
+            Some(path) => path,
+            None => return PlaybackSettings::default(),
+        };
+        let (_, settings) = self.playback.by_handle.entry(source.id).or_insert_with(|| {
+            let settings = read_import_settings(&path)
+                .audio()
+                .map(AudioImportSettings::playback_settings)
+                .unwrap_or_default();
+            (path, settings)
+        });
+        settings.clone()
+    }
+
+    /// Play the audio with its import settings, None if audio is unavailable
+    pub fn play(&mut self, source: Handle<AudioSource>) -> Option<Handle<AudioSink>> {
+        let settings = self.settings(&source);
+        let audio = self.audio.as_ref()?;
+        Some(audio.play_with_settings(source, settings))
+    }
+}
+
+pub fn apply_image_import_settings_system(
+    asset_server: Res<AssetServer>,
+    settings: Option<Res<AssetServerSettings>>,
+    mut imported: ResMut<ImportedAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+) {
+    for event in image_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => {
+                imported.applied.remove(&handle.id);
+                continue;
+            }
+        };
+        if imported.applied.contains_key(&handle.id) {
+            continue;
+        }
+        let path = match asset_file_path(&asset_server, settings.as_deref(), handle.id) {
+            Some(path) => path,
+            None => continue,
+        };
+        if let Some(import_settings) = read_import_settings(&path).image() {
+            if let Some(image) = images.get_mut(handle) {
+                import_settings.apply(image);
+            }
+        }
+        imported.applied.insert(handle.id, path);
+    }
+}
+
+pub fn apply_model_import_settings_system(
+    asset_server: Res<AssetServer>,
+    settings: Option<Res<AssetServerSettings>>,
+    mut imported: ResMut<ImportedAssets>,
+    mut scenes: ResMut<Assets<Scene>>,
+    mut scene_events: EventReader<AssetEvent<Scene>>,
+) {
+    for event in scene_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => {
+                imported.applied.remove(&handle.id);
+                continue;
+            }
+        };
+        if imported.applied.contains_key(&handle.id) {
+            continue;
+        }
+        let path = match asset_file_path(&asset_server, settings.as_deref(), handle.id) {
+            Some(path) => path,
+            None => continue,
+        };
+        if let Some(import_settings) = read_import_settings(&path).model() {
+            if let Some(scene) = scenes.get_mut(handle) {
+                import_settings.apply(scene);
+            }
+        }
+        imported.applied.insert(handle.id, path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_settings_change_sampler_format_and_mipmaps() {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 4,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[200, 100, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let settings = ImageImportSettings {
+            filter: ImageFilter::Nearest,
+            address_mode: ImageAddressMode::Repeat,
+            srgb: false,
+            mipmaps: true,
+        };
+        settings.apply(&mut image);
+
+        assert_eq!(image.texture_descriptor.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(image.texture_descriptor.mip_level_count, 3);
+        // 4x2, 2x1 and 1x1 levels
+        assert_eq!(image.data.len(), (8 + 2 + 1) * 4);
+        assert_eq!(&image.data[image.data.len() - 4..], &[200, 100, 0, 255]);
+        match image.sampler_descriptor {
+            ImageSampler::Descriptor(descriptor) => {
+                assert_eq!(descriptor.mag_filter, FilterMode::Nearest);
+                assert_eq!(descriptor.address_mode_u, AddressMode::Repeat);
+            }
+            ImageSampler::Default => panic!("Sampler was not set"),
+        }
+    }
+
+    #[test]
+    fn settings_are_stored_in_sidecar() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tree.glb");
+        assert_eq!(read_import_settings(&path), ImportSettings::None);
+
+        let guid = AssetMeta::load_or_create(&path).unwrap().guid;
+        let model = ImportSettings::Model(ModelImportSettings { scale: 0.5 });
+        save_import_settings(&path, model).unwrap();
+        assert_eq!(AssetMeta::load_or_create(&path).unwrap().guid, guid);
+        let settings = read_import_settings(&path);
+        assert_eq!(settings.model().map(|model| model.scale), Some(0.5));
+    }
+}
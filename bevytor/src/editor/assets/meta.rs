@@ -0,0 +1,218 @@
+use crate::editor::assets::import::ImportSettings;
+use crate::editor::assets::watcher::AssetChange;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Uuid};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Extension appended to the asset file name, e.g. "albedo.png.meta"
+pub const META_EXTENSION: &str = "meta";
+
+/// Content of the sidecar file stored next to every asset. Directories don't have sidecars
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetMeta {
+    /// Stable identifier of the asset, kept when the asset is renamed or moved
+    pub guid: Uuid,
+    #[serde(default)]
+    pub import_settings: ImportSettings,
+}
+
+impl AssetMeta {
+    pub fn new() -> Self {
+        Self {
+            guid: Uuid::new_v4(),
+            import_settings: ImportSettings::default(),
+        }
+    }
+
+    /// Read sidecar of the asset, creating it when missing
+    pub fn load_or_create(asset_path: &Path) -> Result<Self, String> {
+        let path = meta_path(asset_path);
+        if !path.exists() {
+            let meta = Self::new();
+            meta.save(asset_path)?;
+            return Ok(meta);
+        }
+
+        std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| ron::from_str::<AssetMeta>(&content).map_err(|e| e.to_string()))
+    }
+
+    pub fn save(&self, asset_path: &Path) -> Result<(), String> {
+        let content =
+            ron::ser::to_string_pretty(self, Default::default()).map_err(|e| e.to_string())?;
+        std::fs::write(meta_path(asset_path), content).map_err(|e| e.to_string())
+    }
+}
+
+impl Default for AssetMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Path of the sidecar file of given asset
+pub fn meta_path(asset_path: &Path) -> PathBuf {
+    let mut file_name = asset_path
+        .file_name()
+        .map(OsString::from)
+        .unwrap_or_default();
+    file_name.push(".");
+    file_name.push(META_EXTENSION);
+    asset_path.with_file_name(file_name)
+}
+
+/// Move sidecar after its asset was moved. Missing sidecar or already existing target is
+/// left untouched, e.g. when both files were moved together
+pub fn move_sidecar(from: &Path, to: &Path) -> std::io::Result<()> {
+    let (from, to) = (meta_path(from), meta_path(to));
+    if from.exists() && !to.exists() {
+        std::fs::rename(from, to)?;
+    }
+    Ok(())
+}
+
+/// Rename asset together with its sidecar, so the asset keeps its GUID
+pub fn rename_with_sidecar(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::rename(from, to)?;
+    move_sidecar(from, to)
+}
+
+/// Resource mapping asset GUIDs to their current paths and back. Updated whenever
+/// AssetDirectory changes
+#[derive(Default)]
+pub struct AssetGuidIndex {
+    paths: HashMap<Uuid, PathBuf>,
+    guids: HashMap<PathBuf, Uuid>,
+}
+
+impl AssetGuidIndex {
+    /// Read or create sidecar of the asset and index its GUID. Sidecar copied together with
+    /// its asset gets a fresh GUID, so both copies stay distinguishable
+    pub fn register(&mut self, asset_path: &Path) -> Result<Uuid, String> {
+        self.register_meta(asset_path, AssetMeta::load_or_create(asset_path)?)
+    }
+
+    /// Same as `register`, with the sidecar already read, e.g. by the asset scan task
+    pub fn register_meta(
+        &mut self,
+        asset_path: &Path,
+        mut meta: AssetMeta,
+    ) -> Result<Uuid, String> {
+        let duplicate = self
+            .path(&meta.guid)
+            .is_some_and(|other| other != asset_path && other.exists());
+        if duplicate {
+            meta.guid = Uuid::new_v4();
+            meta.save(asset_path)?;
+        }
+        self.insert(meta.guid, asset_path.to_path_buf());
+        Ok(meta.guid)
+    }
+
+    /// Index GUID under given path, replacing previous path of the GUID
+    pub fn insert(&mut self, guid: Uuid, path: PathBuf) {
+        if let Some(previous) = self.paths.insert(guid, path.clone()) {
+            self.guids.remove(&previous);
+        }
+        if let Some(previous) = self.guids.insert(path, guid) {
+            if previous != guid {
+                self.paths.remove(&previous);
+            }
+        }
+    }
+
+    /// Forget the asset, or all assets in the directory
+    pub fn remove(&mut self, path: &Path) {
+        let paths = &mut self.paths;
+        self.guids.retain(|indexed, guid| {
+            let removed = indexed.starts_with(path);
+            if removed {
+                paths.remove(guid);
+            }
+            !removed
+        });
+    }
+
+    pub fn path(&self, guid: &Uuid) -> Option<&Path> {
+        self.paths.get(guid).map(PathBuf::as_path)
+    }
+
+    pub fn guid(&self, path: &Path) -> Option<Uuid> {
+        self.guids.get(path).copied()
+    }
+
+    /// Drop entries of paths that disappeared from AssetDirectory. Moved assets are already
+    /// registered under their new paths, only leftovers of the old paths are removed
+    pub fn apply_change(&mut self, change: &AssetChange) {
+        match change {
+            AssetChange::Added(_) => {}
+            AssetChange::Removed(path) => self.remove(path),
+            AssetChange::Moved { from, .. } => self.remove(from),
+        }
+    }
+}
+
+/// Component referencing an asset by its GUID, so the reference survives renames.
+/// GUID is stored as a string, as Uuid can't be reflected into scenes
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct AssetReference {
+    pub guid: String,
+}
+
+impl AssetReference {
+    pub fn new(guid: Uuid) -> Self {
+        Self {
+            guid: guid.to_string(),
+        }
+    }
+
+    /// Current path of the referenced asset
+    pub fn resolve<'a>(&self, index: &'a AssetGuidIndex) -> Option<&'a Path> {
+        let guid = Uuid::parse_str(&self.guid).ok()?;
+        index.path(&guid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guid_survives_rename_and_copies_get_new_one() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("albedo.png");
+        std::fs::write(&path, "").unwrap();
+
+        let mut index = AssetGuidIndex::default();
+        let guid = index.register(&path).unwrap();
+        assert!(meta_path(&path).exists());
+        assert_eq!(index.register(&path).unwrap(), guid);
+
+        let renamed = temp_dir.path().join("grass.png");
+        rename_with_sidecar(&path, &renamed).unwrap();
+        assert_eq!(index.register(&renamed).unwrap(), guid);
+        index.apply_change(&AssetChange::Moved {
+            from: path.clone(),
+            to: renamed.clone(),
+        });
+        assert_eq!(index.path(&guid), Some(renamed.as_path()));
+        assert_eq!(index.guid(&path), None);
+        let reference = AssetReference::new(guid);
+        assert_eq!(reference.resolve(&index), Some(renamed.as_path()));
+
+        let copy = temp_dir.path().join("grass_copy.png");
+        std::fs::copy(&renamed, &copy).unwrap();
+        std::fs::copy(meta_path(&renamed), meta_path(&copy)).unwrap();
+        let copy_guid = index.register(&copy).unwrap();
+        assert_ne!(copy_guid, guid);
+        assert_eq!(AssetMeta::load_or_create(&copy).unwrap().guid, copy_guid);
+
+        index.apply_change(&AssetChange::Removed(temp_dir.path().to_path_buf()));
+        assert_eq!(index.path(&guid), None);
+        assert_eq!(index.guid(&copy), None);
+    }
+}
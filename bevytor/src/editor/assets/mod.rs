@@ -1,2 +1,12 @@
 pub mod asset_loader;
 pub mod asset_types;
+pub mod audio;
+pub mod dependencies;
+pub mod file_ops;
+pub mod font;
+pub mod ignore_rules;
+pub mod import;
+pub mod meta;
+pub mod model;
+pub mod scan;
+pub mod watcher;
@@ -0,0 +1,125 @@
+use bevy::asset::Asset;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Kinds of sub-assets contained in glTF model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelSubAssetKind {
+    Scene,
+    Mesh,
+    Material,
+    Animation,
+}
+
+impl Display for ModelSubAssetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ModelSubAssetKind::Scene => "Scene",
+            ModelSubAssetKind::Mesh => "Mesh",
+            ModelSubAssetKind::Material => "Material",
+            ModelSubAssetKind::Animation => "Animation",
+        };
+        f.write_str(kind)
+    }
+}
+
+/// Single sub-asset of glTF model, displayed as a child of the model in the asset browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSubAsset {
+    pub kind: ModelSubAssetKind,
+    /// Name from the glTF file, or the label if the sub-asset is not named
+    pub name: String,
+    /// Bevy asset label, e.g. "Scene0". Used for loading the sub-asset as "model.gltf#Scene0"
+    pub label: String,
+}
+
+/// Collect sub-assets of loaded model, ordered by kind and index
+pub fn model_sub_assets(gltf: &Gltf) -> Vec<ModelSubAsset> {
+    let mut sub_assets = Vec::new();
+    collect(
+        &mut sub_assets,
+        ModelSubAssetKind::Scene,
+        &gltf.scenes,
+        &gltf.named_scenes,
+    );
+    collect(
+        &mut sub_assets,
+        ModelSubAssetKind::Mesh,
+        &gltf.meshes,
+        &gltf.named_meshes,
+    );
+    collect(
+        &mut sub_assets,
+        ModelSubAssetKind::Material,
+        &gltf.materials,
+        &gltf.named_materials,
+    );
+    collect(
+        &mut sub_assets,
+        ModelSubAssetKind::Animation,
+        &gltf.animations,
+        &gltf.named_animations,
+    );
+    sub_assets
+}
+
+fn collect<T: Asset>(
+    sub_assets: &mut Vec<ModelSubAsset>,
+    kind: ModelSubAssetKind,
+    handles: &[Handle<T>],
+    named: &HashMap<String, Handle<T>>,
+) {
+    for (index, handle) in handles.iter().enumerate() {
+        let label = format!("{}{}", kind, index);
+        let name = named
+            .iter()
+            .find(|(_, named_handle)| *named_handle == handle)
+            .map_or_else(|| label.clone(), |(name, _)| name.clone());
+        sub_assets.push(ModelSubAsset { kind, name, label });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::HandleId;
+
+    fn handle<T: Asset>() -> Handle<T> {
+        Handle::weak(HandleId::random::<T>())
+    }
+
+    #[test]
+    fn sub_assets_use_names_and_bevy_labels() {
+        let scenes = vec![handle(), handle()];
+        let materials = vec![handle()];
+        let gltf = Gltf {
+            named_scenes: HashMap::from_iter([("Level".to_string(), scenes[1].clone())]),
+            scenes,
+            meshes: Vec::new(),
+            named_meshes: HashMap::default(),
+            materials,
+            named_materials: HashMap::default(),
+            nodes: Vec::new(),
+            named_nodes: HashMap::default(),
+            default_scene: None,
+            animations: Vec::new(),
+            named_animations: HashMap::default(),
+        };
+
+        let sub_assets = model_sub_assets(&gltf);
+        let described: Vec<(String, String)> = sub_assets
+            .into_iter()
+            .map(|sub_asset| (sub_asset.name, sub_asset.label))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("Scene0".to_string(), "Scene0".to_string()),
+                ("Level".to_string(), "Scene1".to_string()),
+                ("Material0".to_string(), "Material0".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::editor::assets::asset_loader::{AssetDirectory, AssetType};
+use crate::editor::assets::asset_types::{has_extension, AssetLoadContext, AssetTypeRegistry};
+use crate::editor::assets::ignore_rules::AssetIgnoreRules;
+use crate::editor::assets::meta::{AssetGuidIndex, AssetMeta};
+use crate::editor::EditorStateLabel;
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use bevy::utils::HashSet;
+use bevy_egui::EguiContext;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of paths sent from the scan task at once
+const SCAN_BATCH_SIZE: usize = 256;
+/// Time spent every frame on inserting scanned paths into AssetDirectory
+const INSERT_BUDGET: Duration = Duration::from_millis(8);
+
+/// Directory or asset file found by the scan
+pub struct ScannedEntry {
+    pub path: PathBuf,
+    /// Sidecar of the asset read or created by the scan task, None for directories
+    pub meta: Option<Result<AssetMeta, String>>,
+}
+
+/// Creates asset from the scanned file and its sidecar
+pub type CreateScannedAsset<'a> =
+    dyn FnMut(&Path, Result<AssetMeta, String>) -> Option<AssetType> + 'a;
+
+pub enum ScanMessage {
+    Batch(Vec<ScannedEntry>),
+    /// Contains ignore rules loaded during the walk
+    Finished(AssetIgnoreRules),
+}
+
+/// Walk the directory and send found directories and files with given extensions in batches,
+/// skipping ignored ones. Sidecars of the files are read or created here, off the main thread.
+/// Parents are always sent before their content. Stops early when the receiver is dropped
+pub fn scan_directory(
+    root: &Path,
+    extensions: &[&str],
+    batch_size: usize,
+    sender: &Sender<ScanMessage>,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ignore_rules = AssetIgnoreRules::new(root);
+    for entry in ignore_rules.walk(root) {
+        let meta = match entry.file_type().is_dir() {
+            true => None,
+            false if has_extension(extensions, entry.path()) => {
+                Some(AssetMeta::load_or_create(entry.path()))
+            }
+            false => continue,
+        };
+        batch.push(ScannedEntry {
+            path: entry.into_path(),
+            meta,
+        });
+        if batch.len() >= batch_size {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+            if sender.send(ScanMessage::Batch(full)).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = sender.send(ScanMessage::Batch(batch));
+    let _ = sender.send(ScanMessage::Finished(ignore_rules));
+}
+
+/// Resource of the asset directory scan running on the IoTaskPool. Found paths are inserted
+/// into AssetDirectory a few at a time, so the editor stays responsive
+pub struct AssetScan {
+    receiver: Mutex<Receiver<ScanMessage>>,
+    pending: VecDeque<ScannedEntry>,
+    /// Paths inserted by the scan. Nothing else inserts into AssetDirectory until the scan
+    /// finishes, the watcher waits for its ignore rules
+    inserted: HashSet<PathBuf>,
+    discovered: usize,
+    processed: usize,
+    walk_finished: bool,
+    ignore_rules: Option<AssetIgnoreRules>,
+    // Scan is cancelled when the task is dropped
+    _task: Option<Task<()>>,
+}
+
+impl AssetScan {
+    pub fn start(root: PathBuf, extensions: Vec<&'static str>) -> Self {
+        let (sender, receiver) = channel();
+        let task = IoTaskPool::get().spawn(async move {
+            scan_directory(&root, &extensions, SCAN_BATCH_SIZE, &sender);
+        });
+        let mut scan = Self::from_receiver(receiver);
+        scan._task = Some(task);
+        scan
+    }
+
+    fn from_receiver(receiver: Receiver<ScanMessage>) -> Self {
+        Self {
+            receiver: Mutex::new(receiver),
+            pending: VecDeque::new(),
+            inserted: HashSet::default(),
+            discovered: 0,
+            processed: 0,
+            walk_finished: false,
+            ignore_rules: None,
+            _task: None,
+        }
+    }
+
+    /// Insert received paths until the time budget is spent.
+    /// Returns true once all the paths were inserted
+    pub fn insert_pending(
+        &mut self,
+        root: &mut AssetDirectory,
+        create_asset: &mut CreateScannedAsset,
+        budget: Duration,
+    ) -> bool {
+        for message in self.receiver.get_mut().unwrap().try_iter() {
+            match message {
+                ScanMessage::Batch(batch) => {
+                    self.discovered += batch.len();
+                    self.pending.extend(batch);
+                }
+                ScanMessage::Finished(ignore_rules) => {
+                    self.walk_finished = true;
+                    self.ignore_rules = Some(ignore_rules);
+                }
+            }
+        }
+
+        let started = Instant::now();
+        while let Some(entry) = self.pending.pop_front() {
+            self.insert_entry(root, entry, create_asset);
+            self.processed += 1;
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+        self.walk_finished && self.pending.is_empty()
+    }
+
+    /// Insert the entry into its parent directory, which was always inserted before
+    fn insert_entry(
+        &mut self,
+        root: &mut AssetDirectory,
+        entry: ScannedEntry,
+        create_asset: &mut CreateScannedAsset,
+    ) {
+        if !self.inserted.insert(entry.path.clone()) {
+            return;
+        }
+        let parent = match entry
+            .path
+            .parent()
+            .and_then(|parent| root.find_by_path_mut(parent))
+        {
+            Some(parent) => parent,
+            None => return,
+        };
+        match entry.meta {
+            None => parent
+                .children_directories
+                .push(AssetDirectory::new(entry.path)),
+            Some(meta) => parent.assets.extend(create_asset(&entry.path, meta)),
+        }
+    }
+
+    /// Number of inserted and found paths
+    pub fn progress(&self) -> (usize, usize) {
+        (self.processed, self.discovered)
+    }
+
+    /// All the paths were found, so the discovered count is final
+    pub fn walk_finished(&self) -> bool {
+        self.walk_finished
+    }
+
+    /// Ignore rules loaded by the finished walk
+    pub fn take_ignore_rules(&mut self) -> Option<AssetIgnoreRules> {
+        self.ignore_rules.take()
+    }
+}
+
+/// Start scanning the game assets directory
+pub fn start_asset_scan_system(
+    mut commands: Commands,
+    root: Res<AssetDirectory>,
+    asset_types: Res<AssetTypeRegistry>,
+) {
+    info!("Scanning assets in {:?}", root.path);
+    commands.insert_resource(AssetScan::start(
+        root.path.clone(),
+        asset_types.extensions(),
+    ));
+}
+
+/// Insert scanned assets into AssetDirectory. Assets are available once the whole directory
+/// is inserted
+#[allow(clippy::too_many_arguments)]
+pub fn asset_scan_system(
+    mut commands: Commands,
+    scan: Option<ResMut<AssetScan>>,
+    asset_server: Res<AssetServer>,
+    asset_types: Res<AssetTypeRegistry>,
+    mut egui_context: ResMut<EguiContext>,
+    mut images: ResMut<Assets<Image>>,
+    mut guids: ResMut<AssetGuidIndex>,
+    mut root: ResMut<AssetDirectory>,
+    mut editor_state: ResMut<EditorStateLabel>,
+) {
+    let mut scan = match scan {
+        Some(scan) => scan,
+        None => return,
+    };
+
+    let mut context = AssetLoadContext {
+        asset_server: &asset_server,
+        egui_context: &mut egui_context,
+        images: &mut images,
+        guids: &mut guids,
+    };
+    let mut create_asset = |path: &Path, meta: Result<AssetMeta, String>| {
+        asset_types.create_with_meta(path, meta, &mut context)
+    };
+    if scan.insert_pending(&mut root, &mut create_asset, INSERT_BUDGET) {
+        info!("Scanned {} asset paths", scan.progress().1);
+        if let Some(ignore_rules) = scan.take_ignore_rules() {
+            commands.insert_resource(ignore_rules);
+        }
+        commands.remove_resource::<AssetScan>();
+        *editor_state = EditorStateLabel::PostInitializingAssets;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::assets::asset_loader::SceneAssetDescriptor;
+    use crate::editor::assets::meta::meta_path;
+
+    fn create_scene(path: &Path, _meta: Result<AssetMeta, String>) -> Option<AssetType> {
+        Some(AssetType::Scene(SceneAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+        }))
+    }
+
+    #[test]
+    fn scanned_paths_are_inserted_in_batches() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for directory in ["a", "b", "c"] {
+            let directory = temp_dir.path().join(directory);
+            std::fs::create_dir_all(&directory).unwrap();
+            for file in 0..3 {
+                std::fs::write(directory.join(format!("{}.scn.ron", file)), "").unwrap();
+            }
+            std::fs::write(directory.join("notes.md"), "").unwrap();
+        }
+        std::fs::create_dir_all(temp_dir.path().join(".cache")).unwrap();
+        std::fs::write(temp_dir.path().join(".cache/0.scn.ron"), "").unwrap();
+
+        let (sender, receiver) = channel();
+        scan_directory(temp_dir.path(), &["scn.ron"], 4, &sender);
+        assert!(meta_path(&temp_dir.path().join("a/0.scn.ron")).exists());
+        assert!(!meta_path(&temp_dir.path().join("a/notes.md")).exists());
+        let mut scan = AssetScan::from_receiver(receiver);
+        let mut root = AssetDirectory::new(temp_dir.path().to_path_buf());
+        assert!(!scan.insert_pending(&mut root, &mut create_scene, Duration::ZERO));
+        assert_eq!(scan.progress(), (1, 13));
+        assert!(scan.walk_finished());
+
+        while !scan.insert_pending(&mut root, &mut create_scene, Duration::ZERO) {}
+        assert_eq!(scan.progress(), (13, 13));
+        assert_eq!(root.children_directories.len(), 3);
+        assert_eq!(root.iter_assets().count(), 9);
+        assert!(scan.take_ignore_rules().is_some());
+    }
+}
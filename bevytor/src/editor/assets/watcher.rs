@@ -0,0 +1,505 @@
+use crate::editor::assets::asset_loader::{AssetDirectory, AssetType};
+use crate::editor::assets::asset_types::{AssetLoadContext, AssetTypeRegistry};
+use crate::editor::assets::ignore_rules::{is_ignore_file, AssetIgnoreRules};
+use crate::editor::assets::meta::{move_sidecar, AssetGuidIndex};
+use crate::editor::run_if_post_initializing_assets;
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use notify::event::{ModifyKind, RenameMode};
+use notify::poll::PollWatcherConfig;
+use notify::{Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often the polling fallback rescans the asset directory
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// AssetWatcherPlugin keeps AssetDirectory in sync with changes made outside the editor
+pub struct AssetWatcherPlugin;
+impl Plugin for AssetWatcherPlugin {
+    fn build(&self, app: &mut App) {
+        let root = app.world.resource::<AssetDirectory>().path.clone();
+        if let Some(watcher) = AssetWatcher::new(&root) {
+            app.insert_resource(watcher);
+        }
+
+        app.add_event::<AssetAddedEvent>()
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
+            .add_event::<AssetModifiedEvent>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_post_initializing_assets)
+                    .with_system(asset_watcher_system),
+            );
+    }
+}
+
+/// Sent for every directory or asset inserted into AssetDirectory after the initial scan
+pub struct AssetAddedEvent {
+    pub path: PathBuf,
+}
+
+/// Sent when directory or asset is removed from AssetDirectory.
+/// Content of removed directory is not reported separately
+pub struct AssetRemovedEvent {
+    pub path: PathBuf,
+    /// Removed by an editor command, which warns about broken references itself
+    pub from_command: bool,
+}
+
+/// Sent when directory or asset is renamed or moved within AssetDirectory
+pub struct AssetMovedEvent {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Moved by an editor command, which warns about broken references itself
+    pub from_command: bool,
+}
+
+/// Sent when content of an asset already present in AssetDirectory changes on disk
+pub struct AssetModifiedEvent {
+    pub path: PathBuf,
+}
+
+/// Resource receiving file system events of the asset directory. Uses platform specific
+/// watcher and falls back to polling when it is not available
+pub struct AssetWatcher {
+    receiver: Mutex<Receiver<notify::Result<Event>>>,
+    changes: WatchedChanges,
+    // Watcher stops as soon as it is dropped
+    _watcher: Mutex<Box<dyn Watcher + Send>>,
+}
+
+impl AssetWatcher {
+    pub fn new(root: &Path) -> Option<Self> {
+        let (sender, receiver) = channel();
+        let watcher: notify::Result<Box<dyn Watcher + Send>> =
+            notify::recommended_watcher(sender.clone())
+                .and_then(|mut watcher| {
+                    watcher.watch(root, RecursiveMode::Recursive)?;
+                    Ok(Box::new(watcher) as Box<dyn Watcher + Send>)
+                })
+                .or_else(|e| {
+                    warn!("Falling back to polling {:?}: {}", root, e);
+                    let config = PollWatcherConfig {
+                        poll_interval: POLL_INTERVAL,
+                        compare_contents: false,
+                    };
+                    let mut watcher = PollWatcher::with_config(sender, config)?;
+                    watcher.watch(root, RecursiveMode::Recursive)?;
+                    Ok(Box::new(watcher) as Box<dyn Watcher + Send>)
+                });
+
+        match watcher {
+            Ok(watcher) => Some(Self {
+                receiver: Mutex::new(receiver),
+                changes: WatchedChanges::default(),
+                _watcher: Mutex::new(watcher),
+            }),
+            Err(e) => {
+                error!("Failed to watch {:?}: {}", root, e);
+                None
+            }
+        }
+    }
+}
+
+/// File system change, with notify event kinds already interpreted
+#[derive(Debug, PartialEq, Eq)]
+pub enum WatchedChange {
+    /// Something happened to the path, its current state has to be checked on disk
+    Touched(PathBuf),
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+/// Translates notify events into WatchedChanges. Renames are reported by some platforms
+/// as separate "from" and "to" events, which are paired here using their tracker
+#[derive(Default)]
+pub struct WatchedChanges {
+    pending_renames: Vec<PendingRename>,
+}
+
+struct PendingRename {
+    tracker: usize,
+    from: PathBuf,
+    /// Pending rename survived a whole frame without its pair
+    stale: bool,
+}
+
+impl WatchedChanges {
+    pub fn push(&mut self, event: Event, changes: &mut Vec<WatchedChange>) {
+        let tracker = event.tracker();
+        match (event.kind, tracker) {
+            (EventKind::Access(_), _) => {}
+            (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), _)
+                if event.paths.len() == 2 =>
+            {
+                self.pending_renames
+                    .retain(|pending| Some(pending.tracker) != tracker);
+                let mut paths = event.paths.into_iter();
+                changes.push(WatchedChange::Moved {
+                    from: paths.next().unwrap(),
+                    to: paths.next().unwrap(),
+                });
+            }
+            (EventKind::Modify(ModifyKind::Name(RenameMode::From)), Some(tracker)) => {
+                for from in event.paths {
+                    self.pending_renames.push(PendingRename {
+                        tracker,
+                        from,
+                        stale: false,
+                    });
+                }
+            }
+            // Paired "to" event is always followed by the one with both paths
+            (EventKind::Modify(ModifyKind::Name(RenameMode::To)), Some(_)) => {}
+            _ => changes.extend(event.paths.into_iter().map(WatchedChange::Touched)),
+        }
+    }
+
+    /// Called once per frame. Renames without a pair for a whole frame moved the path
+    /// outside the watched directory
+    pub fn flush(&mut self, changes: &mut Vec<WatchedChange>) {
+        for pending in self.pending_renames.iter().filter(|pending| pending.stale) {
+            changes.push(WatchedChange::Touched(pending.from.clone()));
+        }
+        self.pending_renames.retain(|pending| !pending.stale);
+        for pending in self.pending_renames.iter_mut() {
+            pending.stale = true;
+        }
+    }
+}
+
+/// Result of applying WatchedChange to the AssetDirectory
+#[derive(Debug, PartialEq, Eq)]
+pub enum AssetChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+    Moved { from: PathBuf, to: PathBuf },
+}
+
+impl AssetDirectory {
+    /// Update the hierarchy with a change made on disk. Assets are created by `create_asset`,
+    /// ignored paths are skipped
+    pub fn apply_watched_change(
+        &mut self,
+        change: WatchedChange,
+        create_asset: &mut dyn FnMut(&Path) -> Option<AssetType>,
+        ignore_rules: &mut AssetIgnoreRules,
+    ) -> Vec<AssetChange> {
+        match change {
+            WatchedChange::Touched(path) if is_ignore_file(&path) => match path.parent() {
+                Some(directory) => self.reload_ignore_rules(directory, create_asset, ignore_rules),
+                None => Vec::new(),
+            },
+            WatchedChange::Touched(path) if path == self.path => Vec::new(),
+            WatchedChange::Touched(path) if ignore_rules.is_ignored(&path, path.is_dir()) => {
+                Vec::new()
+            }
+            WatchedChange::Touched(path) if path.exists() => self
+                .insert_recursive(&path, create_asset, ignore_rules)
+                .into_iter()
+                .map(AssetChange::Added)
+                .collect(),
+            WatchedChange::Touched(path) => match self.remove_path(&path) {
+                true => vec![AssetChange::Removed(path)],
+                false => Vec::new(),
+            },
+            WatchedChange::Moved { from, to } if is_ignore_file(&from) || is_ignore_file(&to) => {
+                let mut changes = self.apply_watched_change(
+                    WatchedChange::Touched(from),
+                    create_asset,
+                    ignore_rules,
+                );
+                changes.extend(self.apply_watched_change(
+                    WatchedChange::Touched(to),
+                    create_asset,
+                    ignore_rules,
+                ));
+                changes
+            }
+            WatchedChange::Moved { from, to } => {
+                let removed = self.remove_path(&from);
+                let added = match to.starts_with(&self.path) {
+                    true => self.insert_recursive(&to, create_asset, ignore_rules),
+                    false => Vec::new(),
+                };
+                match (removed, added.is_empty()) {
+                    (true, false) => vec![AssetChange::Moved { from, to }],
+                    (true, true) => vec![AssetChange::Removed(from)],
+                    // E.g. temporary file renamed to supported asset by an external tool
+                    (false, _) => added.into_iter().map(AssetChange::Added).collect(),
+                }
+            }
+        }
+    }
+
+    /// Ignore file of the directory changed. Newly ignored paths are removed and paths which
+    /// are no longer ignored are inserted
+    fn reload_ignore_rules(
+        &mut self,
+        directory: &Path,
+        create_asset: &mut dyn FnMut(&Path) -> Option<AssetType>,
+        ignore_rules: &mut AssetIgnoreRules,
+    ) -> Vec<AssetChange> {
+        ignore_rules.load(directory);
+        let ignored = match self.find_by_path(directory) {
+            Some(directory) => directory.ignored_paths(ignore_rules),
+            // The directory itself is ignored
+            None => return Vec::new(),
+        };
+        let mut changes: Vec<AssetChange> = ignored
+            .into_iter()
+            .filter(|path| self.remove_path(path))
+            .map(AssetChange::Removed)
+            .collect();
+        changes.extend(
+            self.insert_recursive(directory, create_asset, ignore_rules)
+                .into_iter()
+                .map(AssetChange::Added),
+        );
+        changes
+    }
+}
+
+/// Apply file system changes to the AssetDirectory and report them through asset events
+#[allow(clippy::too_many_arguments)]
+pub fn asset_watcher_system(
+    watcher: Option<ResMut<AssetWatcher>>,
+    asset_server: Res<AssetServer>,
+    asset_types: Res<AssetTypeRegistry>,
+    mut egui_context: ResMut<EguiContext>,
+    mut images: ResMut<Assets<Image>>,
+    mut guids: ResMut<AssetGuidIndex>,
+    mut root: ResMut<AssetDirectory>,
+    ignore_rules: Option<ResMut<AssetIgnoreRules>>,
+    mut added_writer: EventWriter<AssetAddedEvent>,
+    mut removed_writer: EventWriter<AssetRemovedEvent>,
+    mut moved_writer: EventWriter<AssetMovedEvent>,
+    mut modified_writer: EventWriter<AssetModifiedEvent>,
+) {
+    let mut watcher = match watcher {
+        Some(watcher) => watcher,
+        None => return,
+    };
+    let watcher = watcher.as_mut();
+    // Changes wait in the channel until the initial scan loads the ignore rules
+    let mut ignore_rules = match ignore_rules {
+        Some(ignore_rules) => ignore_rules,
+        None => return,
+    };
+
+    let mut changes = Vec::new();
+    for event in watcher.receiver.get_mut().unwrap().try_iter() {
+        match event {
+            Ok(event) => watcher.changes.push(event, &mut changes),
+            Err(e) => warn!("Asset watcher error: {}", e),
+        }
+    }
+    watcher.changes.flush(&mut changes);
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut context = AssetLoadContext {
+        asset_server: &asset_server,
+        egui_context: &mut egui_context,
+        images: &mut images,
+        guids: &mut guids,
+    };
+    for change in changes {
+        // Sidecar has to follow its asset before the asset is recreated, so it keeps its GUID
+        if let WatchedChange::Moved { from, to } = &change {
+            if let Err(e) = move_sidecar(from, to) {
+                warn!("Failed to move meta file of {:?}: {}", from, e);
+            }
+        }
+        // Touched asset which stays in the hierarchy had its content modified
+        let touched_asset = match &change {
+            WatchedChange::Touched(path) if root.find_asset(path).is_some() => Some(path.clone()),
+            _ => None,
+        };
+        let mut create_asset = |path: &Path| asset_types.create(path, &mut context);
+        let asset_changes = root.apply_watched_change(change, &mut create_asset, &mut ignore_rules);
+        if let (Some(path), true) = (touched_asset, asset_changes.is_empty()) {
+            modified_writer.send(AssetModifiedEvent { path });
+        }
+        for asset_change in asset_changes {
+            context.guids.apply_change(&asset_change);
+            match asset_change {
+                AssetChange::Added(path) => added_writer.send(AssetAddedEvent { path }),
+                AssetChange::Removed(path) => removed_writer.send(AssetRemovedEvent {
+                    path,
+                    from_command: false,
+                }),
+                AssetChange::Moved { from, to } => moved_writer.send(AssetMovedEvent {
+                    from,
+                    to,
+                    from_command: false,
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::assets::asset_loader::SceneAssetDescriptor;
+    use notify::event::CreateKind;
+
+    fn create_scene(path: &Path) -> Option<AssetType> {
+        Some(AssetType::Scene(SceneAssetDescriptor {
+            name: path.file_name()?.to_os_string(),
+            path: path.to_path_buf(),
+        }))
+    }
+
+    fn rename(mode: RenameMode, tracker: usize, path: &Path) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Name(mode)))
+            .set_tracker(tracker)
+            .add_path(path.to_path_buf())
+    }
+
+    #[test]
+    fn added_directories_are_inserted_with_content_once() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut root = AssetDirectory::new(temp_dir.path().to_path_buf());
+        let mut rules = AssetIgnoreRules::new(temp_dir.path());
+        let textures = temp_dir.path().join("textures");
+        std::fs::create_dir_all(textures.join("grass")).unwrap();
+        std::fs::write(textures.join("grass/albedo.png"), "").unwrap();
+
+        let changes = root.apply_watched_change(
+            WatchedChange::Touched(textures.clone()),
+            &mut create_scene,
+            &mut rules,
+        );
+        assert_eq!(changes.len(), 3);
+        assert!(root.contains(&textures.join("grass/albedo.png")));
+
+        let changes = root.apply_watched_change(
+            WatchedChange::Touched(textures.join("grass/albedo.png")),
+            &mut create_scene,
+            &mut rules,
+        );
+        assert!(changes.is_empty());
+        assert_eq!(
+            root.find_by_path(&textures.join("grass"))
+                .unwrap()
+                .assets
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn moved_directory_is_reinserted_under_new_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut root = AssetDirectory::new(temp_dir.path().to_path_buf());
+        let mut rules = AssetIgnoreRules::new(temp_dir.path());
+        let from = temp_dir.path().join("textures");
+        let to = temp_dir.path().join("materials");
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::write(from.join("albedo.png"), "").unwrap();
+        root.insert_recursive(&from, &mut create_scene, &mut rules);
+
+        std::fs::rename(&from, &to).unwrap();
+        let mut changes = Vec::new();
+        let mut watched = WatchedChanges::default();
+        watched.push(rename(RenameMode::From, 1, &from), &mut changes);
+        watched.push(rename(RenameMode::To, 1, &to), &mut changes);
+        watched.push(
+            rename(RenameMode::Both, 1, &from).add_path(to.clone()),
+            &mut changes,
+        );
+        assert_eq!(changes.len(), 1);
+
+        let changes = root.apply_watched_change(changes.remove(0), &mut create_scene, &mut rules);
+        assert_eq!(
+            changes,
+            vec![AssetChange::Moved {
+                from: from.clone(),
+                to: to.clone()
+            }]
+        );
+        assert!(!root.contains(&from));
+        assert_eq!(
+            root.find_by_path(&to).unwrap().assets[0].get_path(),
+            to.join("albedo.png")
+        );
+    }
+
+    #[test]
+    fn unpaired_rename_is_reported_after_a_frame() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut root = AssetDirectory::new(temp_dir.path().to_path_buf());
+        let mut rules = AssetIgnoreRules::new(temp_dir.path());
+        let path = temp_dir.path().join("level.scn.ron");
+        std::fs::write(&path, "").unwrap();
+        let mut watched = WatchedChanges::default();
+        let mut changes = Vec::new();
+        watched.push(
+            Event::new(EventKind::Create(CreateKind::File)).add_path(path.clone()),
+            &mut changes,
+        );
+        assert_eq!(
+            root.apply_watched_change(changes.remove(0), &mut create_scene, &mut rules),
+            vec![AssetChange::Added(path.clone())]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        watched.push(rename(RenameMode::From, 7, &path), &mut changes);
+        watched.flush(&mut changes);
+        assert!(changes.is_empty());
+        watched.flush(&mut changes);
+        assert_eq!(changes, vec![WatchedChange::Touched(path.clone())]);
+        assert_eq!(
+            root.apply_watched_change(changes.remove(0), &mut create_scene, &mut rules),
+            vec![AssetChange::Removed(path)]
+        );
+    }
+
+    #[test]
+    fn changed_ignore_file_updates_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut root = AssetDirectory::new(temp_dir.path().to_path_buf());
+        let mut rules = AssetIgnoreRules::new(temp_dir.path());
+        let cache = temp_dir.path().join("cache");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("lightmap.png"), "").unwrap();
+        root.insert_recursive(temp_dir.path(), &mut create_scene, &mut rules);
+        assert!(root.contains(&cache.join("lightmap.png")));
+
+        let ignore_file = temp_dir.path().join(".bevytorignore");
+        std::fs::write(&ignore_file, "cache/\n").unwrap();
+        let changes = root.apply_watched_change(
+            WatchedChange::Touched(ignore_file.clone()),
+            &mut create_scene,
+            &mut rules,
+        );
+        assert_eq!(changes, vec![AssetChange::Removed(cache.clone())]);
+        assert!(!root.contains(&cache));
+        assert!(root
+            .apply_watched_change(
+                WatchedChange::Touched(cache.join("lightmap.png")),
+                &mut create_scene,
+                &mut rules,
+            )
+            .is_empty());
+
+        std::fs::remove_file(&ignore_file).unwrap();
+        let changes = root.apply_watched_change(
+            WatchedChange::Touched(ignore_file),
+            &mut create_scene,
+            &mut rules,
+        );
+        assert_eq!(changes.len(), 2);
+        assert!(root.contains(&cache.join("lightmap.png")));
+    }
+}
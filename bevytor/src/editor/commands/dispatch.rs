@@ -0,0 +1,282 @@
+use crate::editor::commands::{
+    next_command_sequence, Command, CommandAny, CommandError, CommandExecuteDirection,
+    CommandFailedEvent, CommandSource, ExecuteCommandEvent, UndoRedoCommandEvent,
+};
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::panic::Location;
+
+/// Command that knows how to apply and revert itself. Commands registered with
+/// `add_editor_command` are sent through CommandWriter, the editor takes care of executing
+/// them, storing them in the CommandQueue and undoing/redoing them
+pub trait ApplyCommand: Command {
+    /// Execute the command. Called on initial execution and on redo
+    fn apply(&self, world: &mut World) -> Result<(), CommandError>;
+    /// Revert changes made by `apply`. Called on undo
+    fn revert(&self, world: &mut World) -> Result<(), CommandError>;
+}
+
+/// Several commands of the same type executed as a single undo step, e.g. bulk operation on
+/// selected assets. Commands are applied in order and reverted in reverse order. If any of them
+/// fails, the already applied ones are reverted, so the batch is never applied partially
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandBatch<T> {
+    pub commands: Vec<T>,
+}
+
+impl<T: ApplyCommand + Clone> Command for CommandBatch<T> {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<CommandBatch<T>>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .commands
+                .iter()
+                .map(|command| command.estimated_size())
+                .sum::<usize>()
+    }
+
+    fn describe(&self) -> String {
+        match self.commands.split_first() {
+            Some((first, [])) => first.describe(),
+            Some((first, rest)) => format!("{} (+{} more)", first.describe(), rest.len()),
+            None => "Empty batch".into(),
+        }
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        let mut resources: Vec<TypeId> = self
+            .commands
+            .iter()
+            .flat_map(|command| command.affected_resources())
+            .collect();
+        resources.sort();
+        resources.dedup();
+        resources
+    }
+}
+
+impl<T: ApplyCommand + Clone> ApplyCommand for CommandBatch<T> {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        for (index, command) in self.commands.iter().enumerate() {
+            if let Err(e) = command.apply(world) {
+                for applied in self.commands[..index].iter().rev() {
+                    if let Err(e) = applied.revert(world) {
+                        error!("Failed to roll back \"{}\": {}", applied.describe(), e);
+                    }
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        for (index, command) in self.commands.iter().enumerate().rev() {
+            if let Err(e) = command.revert(world) {
+                for reverted in self.commands[index + 1..].iter() {
+                    if let Err(e) = reverted.apply(world) {
+                        error!("Failed to roll back \"{}\": {}", reverted.describe(), e);
+                    }
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Command waiting for the dispatcher. Source and sequence id are captured when it is sent
+pub struct SendCommandEvent {
+    inner: Box<dyn CommandAny>,
+    source: CommandSource,
+    sequence: u64,
+}
+
+impl SendCommandEvent {
+    /// Create event with the caller as its source
+    #[track_caller]
+    pub fn new<T: ApplyCommand>(command: T) -> Self {
+        Self {
+            inner: Box::new(command),
+            source: CommandSource::Caller(Location::caller()),
+            sequence: next_command_sequence(),
+        }
+    }
+}
+
+/// Writer of commands registered with `add_editor_command`
+#[derive(SystemParam)]
+pub struct CommandWriter<'w, 's> {
+    writer: EventWriter<'w, 's, SendCommandEvent>,
+}
+
+impl<'w, 's> CommandWriter<'w, 's> {
+    /// Send command to the dispatcher, the calling code is recorded as its source
+    #[track_caller]
+    pub fn send<T: ApplyCommand>(&mut self, command: T) {
+        self.writer.send(SendCommandEvent::new(command));
+    }
+}
+
+/// App extension registering ApplyCommand handlers
+pub trait AddEditorCommand {
+    fn add_editor_command<T: ApplyCommand>(&mut self) -> &mut Self;
+}
+
+impl AddEditorCommand for App {
+    fn add_editor_command<T: ApplyCommand>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<CommandHandlers>() {
+            self.add_event::<SendCommandEvent>()
+                .add_event::<ExecuteCommandEvent>()
+                .add_event::<UndoRedoCommandEvent>()
+                .add_event::<CommandFailedEvent>()
+                .init_resource::<CommandHandlers>()
+                .add_stage_after(
+                    CoreStage::Update,
+                    DispatchCommandsStage,
+                    SystemStage::single_threaded()
+                        .with_system(dispatch_commands_system.exclusive_system()),
+                );
+        }
+
+        self.world
+            .resource_mut::<CommandHandlers>()
+            .by_type
+            .entry(TypeId::of::<T>())
+            .or_insert(CommandHandler {
+                apply: |command, world| downcast::<T>(command).apply(world),
+                revert: |command, world| downcast::<T>(command).revert(world),
+            });
+        self
+    }
+}
+
+/// Stage running after CoreStage::Update, so commands sent during Update are applied in the
+/// same frame and reach the CommandQueue in CoreStage::PostUpdate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct DispatchCommandsStage;
+
+fn downcast<T: ApplyCommand>(command: &dyn CommandAny) -> &T {
+    command
+        .as_any()
+        .downcast_ref()
+        .expect("Command is dispatched to the handler of its own type")
+}
+
+/// Type erased ApplyCommand implementation of a single command type
+struct CommandHandler {
+    apply: fn(&dyn CommandAny, &mut World) -> Result<(), CommandError>,
+    revert: fn(&dyn CommandAny, &mut World) -> Result<(), CommandError>,
+}
+
+/// Handlers of all the commands registered with `add_editor_command`
+#[derive(Default)]
+struct CommandHandlers {
+    by_type: HashMap<TypeId, CommandHandler>,
+    undo_redo: ManualEventReader<UndoRedoCommandEvent>,
+    commands: ManualEventReader<SendCommandEvent>,
+}
+
+impl CommandHandlers {
+    fn execute(
+        &self,
+        command: &dyn CommandAny,
+        mode: CommandExecuteDirection,
+        world: &mut World,
+    ) -> Result<(), CommandError> {
+        let handler = self.by_type.get(&command.command_type()).ok_or_else(|| {
+            CommandError::Other(format!(
+                "No handler registered for \"{}\"",
+                command.describe()
+            ))
+        })?;
+        match mode {
+            CommandExecuteDirection::Undo => (handler.revert)(command, world),
+            CommandExecuteDirection::Redo => (handler.apply)(command, world),
+        }
+    }
+}
+
+/// Exclusive system applying newly sent commands and undoing/redoing the ones stored in the
+/// CommandQueue. Undo/redo requests are handled first, in the order they were sent, new commands
+/// after them ordered by source and sequence id. Once an undo/redo step fails, the following
+/// steps are skipped. Results are reported through ExecuteCommandEvent and CommandFailedEvent
+fn dispatch_commands_system(world: &mut World) {
+    world.resource_scope(|world, mut handlers: Mut<CommandHandlers>| {
+        let undo_redo: Vec<UndoRedoCommandEvent> = handlers
+            .undo_redo
+            .iter(world.resource::<Events<UndoRedoCommandEvent>>())
+            .map(|event| UndoRedoCommandEvent {
+                inner: event.consume(),
+                mode: event.mode,
+                step: event.step,
+            })
+            .collect();
+        // Children of the step being taken which were already undone/redone
+        let mut step_done: Vec<&UndoRedoCommandEvent> = Vec::new();
+        let mut failed = false;
+        for event in undo_redo.iter() {
+            // Steps following a failed one are not taken, the CommandQueue moves its pointer
+            // right next to the failed step
+            if failed && event.step.is_some() {
+                continue;
+            }
+            let same_step =
+                |done: &&UndoRedoCommandEvent| done.step == event.step && done.mode == event.mode;
+            if !step_done.last().is_none_or(same_step) {
+                step_done.clear();
+            }
+
+            match handlers.execute(event.inner.as_ref(), event.mode, world) {
+                Ok(_) => step_done.push(event),
+                Err(e) => {
+                    // Step is never taken partially, handled compound children are rolled back
+                    for done in step_done.drain(..).rev() {
+                        let mode = done.mode.opposite();
+                        if let Err(e) = handlers.execute(done.inner.as_ref(), mode, world) {
+                            error!("Failed to roll back \"{}\": {}", done.inner.describe(), e);
+                        }
+                    }
+                    failed |= event.step.is_some();
+                    world.send_event(event.failed(e));
+                }
+            }
+        }
+
+        let mut commands: Vec<SendCommandEvent> = handlers
+            .commands
+            .iter(world.resource::<Events<SendCommandEvent>>())
+            .map(|event| SendCommandEvent {
+                inner: event.inner.recreate(),
+                source: event.source,
+                sequence: event.sequence,
+            })
+            .collect();
+        commands.sort_by_key(|command| (command.source, command.sequence));
+        for command in commands {
+            let mode = CommandExecuteDirection::Redo;
+            match handlers.execute(command.inner.as_ref(), mode, world) {
+                Ok(_) => world.send_event(ExecuteCommandEvent {
+                    inner: command.inner,
+                    source: command.source,
+                    sequence: command.sequence,
+                }),
+                Err(e) => {
+                    let failure = CommandFailedEvent::execution(command.inner.as_ref(), e);
+                    world.send_event(failure);
+                }
+            }
+        }
+    });
+}
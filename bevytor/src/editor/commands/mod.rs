@@ -0,0 +1,1022 @@
+use crate::editor::editor_directory;
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use persistence::{
+    load_command_history_system, save_action_system, save_command_history_system,
+    CommandHistoryFile, CommandHistoryState, CommandRegistry, SaveCommandHistoryEvent,
+    COMMAND_HISTORY_FILE,
+};
+use std::any::{Any, TypeId};
+use std::fmt::{Display, Formatter};
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+pub mod dispatch;
+pub mod persistence;
+
+/// Auto trait enabling command downcasting
+pub trait CommandAny: Command + Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T> CommandAny for T
+where
+    T: Command + Any,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Command trait used by undo/redo chain
+pub trait Command: Send + Sync + 'static {
+    fn recreate(&self) -> Box<dyn CommandAny>;
+    fn command_type(&self) -> TypeId;
+
+    /// Try to absorb `next` command into this one, so both are undone in a single step.
+    /// `next` is guaranteed to have the same `command_type()`. Returns true if merge took place
+    fn merge(&mut self, _next: &dyn CommandAny) -> bool {
+        false
+    }
+
+    /// Estimated memory used by this command, in bytes. Used for limiting the undo history.
+    /// Commands owning heap allocated data should override it
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
+    /// Human readable description displayed in the history panel
+    fn describe(&self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        type_name.rsplit("::").next().unwrap_or(type_name).into()
+    }
+
+    /// Resources modified by this command. Used for detecting systems racing on the same
+    /// resource within a single frame
+    fn affected_resources(&self) -> Vec<TypeId> {
+        Vec::new()
+    }
+}
+
+/// Used by undo/redo chain to specify how the command should be repeated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandExecuteDirection {
+    Undo,
+    Redo,
+}
+
+impl CommandExecuteDirection {
+    pub fn opposite(&self) -> Self {
+        match self {
+            CommandExecuteDirection::Undo => CommandExecuteDirection::Redo,
+            CommandExecuteDirection::Redo => CommandExecuteDirection::Undo,
+        }
+    }
+}
+
+/// Source of command sequence ids, shared by all the senders
+static NEXT_COMMAND_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Sender of a command. Commands sent within one frame are ordered by source and then by
+/// sequence id, so the history doesn't depend on the order in which systems were scheduled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommandSource {
+    /// Label of the system which executed the command itself
+    Label(&'static str),
+    /// Code which sent the command through CommandWriter
+    Caller(&'static Location<'static>),
+}
+
+impl Display for CommandSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandSource::Label(label) => write!(f, "{}", label),
+            CommandSource::Caller(location) => write!(f, "{}", location),
+        }
+    }
+}
+
+impl From<&'static str> for CommandSource {
+    fn from(label: &'static str) -> Self {
+        CommandSource::Label(label)
+    }
+}
+
+/// Systems relying on undo/redo should send all their executed commands through this Event
+pub struct ExecuteCommandEvent {
+    pub inner: Box<dyn CommandAny>,
+    pub source: CommandSource,
+    /// Monotonically increasing id assigned when the command is sent
+    sequence: u64,
+}
+impl ExecuteCommandEvent {
+    pub fn new(inner: Box<dyn CommandAny>, source: impl Into<CommandSource>) -> Self {
+        Self {
+            inner,
+            source: source.into(),
+            sequence: next_command_sequence(),
+        }
+    }
+
+    pub fn consume(&self) -> Box<dyn CommandAny> {
+        self.inner.recreate()
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+fn next_command_sequence() -> u64 {
+    NEXT_COMMAND_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Undo/Redo system resends commands retrieved through CommandExecutedEvents
+pub struct UndoRedoCommandEvent {
+    pub inner: Box<dyn CommandAny>,
+    pub mode: CommandExecuteDirection,
+    /// Index of the CommandQueue item this command belongs to.
+    /// None for commands undone due to aborted transaction
+    pub step: Option<usize>,
+}
+impl UndoRedoCommandEvent {
+    pub fn consume(&self) -> Box<dyn CommandAny> {
+        self.inner.recreate()
+    }
+
+    pub fn cmd_type(&self) -> TypeId {
+        self.inner.command_type()
+    }
+
+    /// Create CommandFailedEvent reporting that this undo/redo couldn't be performed
+    pub fn failed(&self, error: impl Into<CommandError>) -> CommandFailedEvent {
+        CommandFailedEvent {
+            command_type: self.cmd_type(),
+            description: self.inner.describe(),
+            direction: Some(self.mode),
+            step: self.step,
+            error: error.into(),
+        }
+    }
+}
+
+/// Error returned by command handlers
+#[derive(Debug)]
+pub enum CommandError {
+    Io(std::io::Error),
+    Other(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Io(e) => write!(f, "{}", e),
+            CommandError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        CommandError::Io(e)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Other(message)
+    }
+}
+
+/// Sent by handler systems when a command couldn't be executed, undone or redone.
+/// Failed undo/redo moves the CommandQueue pointer back, as if the step was never taken
+pub struct CommandFailedEvent {
+    pub command_type: TypeId,
+    pub description: String,
+    /// None if the initial execution failed, such command never reaches the CommandQueue
+    pub direction: Option<CommandExecuteDirection>,
+    /// Index of the CommandQueue item that failed
+    pub step: Option<usize>,
+    pub error: CommandError,
+}
+
+impl CommandFailedEvent {
+    /// Create event reporting failed initial execution of the command
+    pub fn execution(command: &dyn CommandAny, error: impl Into<CommandError>) -> Self {
+        Self {
+            command_type: command.command_type(),
+            description: command.describe(),
+            direction: None,
+            step: None,
+            error: error.into(),
+        }
+    }
+}
+
+impl Display for CommandFailedEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.direction {
+            Some(direction) => write!(
+                f,
+                "{:?} of \"{}\" failed: {}",
+                direction, self.description, self.error
+            ),
+            None => write!(f, "\"{}\" failed: {}", self.description, self.error),
+        }
+    }
+}
+
+/// Command grouping several commands into a single undo step. Children are redone in order
+/// and undone in reverse order, each one reaching its own handler. If any child fails, the ones
+/// already handled are rolled back
+#[derive(Default)]
+pub struct CompoundCommand {
+    pub children: Vec<Box<dyn CommandAny>>,
+}
+
+impl Command for CompoundCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(Self {
+            children: self.children.iter().map(|child| child.recreate()).collect(),
+        })
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<CompoundCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .children
+                .iter()
+                .map(|child| child.estimated_size())
+                .sum::<usize>()
+    }
+
+    fn describe(&self) -> String {
+        match self.children.split_first() {
+            Some((first, [])) => first.describe(),
+            Some((first, rest)) => format!("{} (+{} more)", first.describe(), rest.len()),
+            None => "Empty group".into(),
+        }
+    }
+}
+
+/// Send UndoRedoCommandEvent for given command. CompoundCommands are unrolled, so every child
+/// is delivered separately in the order required by `mode`
+fn send_undo_redo(
+    command: &dyn CommandAny,
+    mode: CommandExecuteDirection,
+    step: Option<usize>,
+    commands_writer: &mut EventWriter<UndoRedoCommandEvent>,
+) {
+    match command.as_any().downcast_ref::<CompoundCommand>() {
+        Some(compound) => match mode {
+            CommandExecuteDirection::Undo => {
+                for child in compound.children.iter().rev() {
+                    send_undo_redo(child.as_ref(), mode, step, commands_writer);
+                }
+            }
+            CommandExecuteDirection::Redo => {
+                for child in compound.children.iter() {
+                    send_undo_redo(child.as_ref(), mode, step, commands_writer);
+                }
+            }
+        },
+        None => commands_writer.send(UndoRedoCommandEvent {
+            inner: command.recreate(),
+            mode,
+            step,
+        }),
+    }
+}
+
+/// Events used for bundling multiple executed commands into one undo step.
+/// Within a single frame Begin is processed before ExecuteCommandEvents and Commit/Abort after
+/// them, so a system can send the whole transaction at once. Transactions can be nested
+pub enum CommandTransactionEvent {
+    /// Start collecting executed commands into a CompoundCommand
+    Begin,
+    /// Close the innermost transaction and store it as a single undo step
+    Commit,
+    /// Close the innermost transaction and undo all the commands executed within it
+    Abort,
+}
+
+/// Default time window in which consecutive commands of the same type can be merged.
+/// Long enough to cover walking through directories by double clicking them
+pub const DEFAULT_MERGE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Limits of the Undo/Redo chain. Oldest commands are evicted first once any of them is exceeded
+#[derive(Clone, Copy, Debug)]
+pub struct CommandHistoryLimits {
+    /// Maximum number of undo steps
+    pub max_commands: Option<usize>,
+    /// Maximum estimated size of all the stored commands, in bytes
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for CommandHistoryLimits {
+    fn default() -> Self {
+        Self {
+            max_commands: Some(1000),
+            max_bytes: Some(16 * 1024 * 1024),
+        }
+    }
+}
+
+/// Resource for Undo/Redo chain manipulation
+pub struct CommandQueue {
+    /// Commands stored in the chain
+    pub items: Vec<Box<dyn CommandAny>>,
+    /// Index of command that will be "undoed" after pressing Ctrl+z / Cmd+z
+    pub pointer: Option<usize>,
+    /// Commands inserted within this window after the previous one are candidates for merging.
+    /// `None` disables merging
+    pub merge_window: Option<Duration>,
+    /// History size limits, enforced on every insert
+    pub limits: CommandHistoryLimits,
+    /// Time of the last insertion, used together with `merge_window`
+    last_insert: Option<Instant>,
+    /// Open transactions, innermost last
+    transactions: Vec<CompoundCommand>,
+}
+
+impl Default for CommandQueue {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            pointer: None,
+            merge_window: Some(DEFAULT_MERGE_WINDOW),
+            limits: CommandHistoryLimits::default(),
+            last_insert: None,
+            transactions: Vec::new(),
+        }
+    }
+}
+impl Display for CommandQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CommandQueue lenght: {}, pointer position: {:?}",
+            self.items.len(),
+            self.pointer
+        )
+    }
+}
+
+impl CommandQueue {
+    /// Insert incoming command at the end of the Undo/Redo chain.
+    /// All Commands stored after the pointer (with index > pointer) will be removed.
+    /// If the command can be merged with the last one, no new undo step is created.
+    /// While a transaction is open the command is added to it instead
+    pub fn insert(&mut self, command: Box<dyn CommandAny>) {
+        self.insert_at(command, Instant::now());
+    }
+
+    /// `insert` with explicit insertion time, which decides about merging
+    fn insert_at(&mut self, command: Box<dyn CommandAny>, now: Instant) {
+        if let Some(transaction) = self.transactions.last_mut() {
+            transaction.children.push(command);
+            return;
+        }
+
+        let last_insert = self.last_insert.replace(now);
+        if self.try_merge(command.as_ref(), last_insert, now) {
+            self.enforce_limits();
+            return;
+        }
+
+        if self.items.is_empty() {
+            self.items.push(command);
+            self.pointer = Some(self.items.len() - 1);
+            self.enforce_limits();
+            return;
+        }
+
+        match self.pointer {
+            Some(ptr) => {
+                if ptr < self.items.len() - 1 {
+                    self.items.truncate(ptr + 1);
+                }
+                self.items.push(command);
+                self.pointer = Some(self.items.len() - 1);
+                self.enforce_limits();
+            }
+            None => {
+                self.items.clear();
+                self.insert_at(command, now);
+            }
+        }
+    }
+
+    /// Estimated size of all the commands stored in the chain, in bytes
+    pub fn estimated_size(&self) -> usize {
+        self.items.iter().map(|item| item.estimated_size()).sum()
+    }
+
+    /// Evict oldest commands until the chain satisfies `limits`. The newest command is always kept
+    pub fn enforce_limits(&mut self) {
+        let mut size = self.estimated_size();
+        while self.items.len() > 1 {
+            let too_many = matches!(self.limits.max_commands, Some(max) if self.items.len() > max);
+            let too_big = matches!(self.limits.max_bytes, Some(max) if size > max);
+            if !too_many && !too_big {
+                break;
+            }
+
+            let evicted = self.items.remove(0);
+            size -= evicted.estimated_size();
+            self.pointer = match self.pointer {
+                Some(ptr) if ptr > 0 => Some(ptr - 1),
+                _ => None,
+            };
+        }
+    }
+
+    /// Merge `command` into the command under the pointer. Only possible when the pointer
+    /// is at the end of the chain and the previous insert happened within `merge_window`
+    fn try_merge(
+        &mut self,
+        command: &dyn CommandAny,
+        last_insert: Option<Instant>,
+        now: Instant,
+    ) -> bool {
+        let within_window = match (self.merge_window, last_insert) {
+            (Some(window), Some(last)) => now.duration_since(last) <= window,
+            _ => false,
+        };
+        if !within_window {
+            return false;
+        }
+
+        match self.pointer {
+            Some(ptr) if ptr == self.items.len() - 1 => {
+                let last = &mut self.items[ptr];
+                last.command_type() == command.command_type() && last.merge(command)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if commands are currently collected into a transaction
+    pub fn in_transaction(&self) -> bool {
+        !self.transactions.is_empty()
+    }
+
+    /// Start new, possibly nested, transaction
+    pub fn begin_transaction(&mut self) {
+        self.transactions.push(CompoundCommand::default());
+    }
+
+    /// Close the innermost transaction. Nested transactions end up as a child of their parent,
+    /// outermost one is inserted into the chain. Empty transactions are dropped
+    pub fn commit_transaction(&mut self) {
+        match self.transactions.pop() {
+            Some(transaction) if transaction.children.is_empty() => (),
+            Some(transaction) => self.insert(Box::new(transaction)),
+            None => warn!("Commit requested without open transaction"),
+        }
+    }
+
+    /// Close the innermost transaction, undoing all the commands executed within it
+    pub fn abort_transaction(&mut self, commands_writer: &mut EventWriter<UndoRedoCommandEvent>) {
+        match self.transactions.pop() {
+            Some(transaction) => send_undo_redo(
+                &transaction,
+                CommandExecuteDirection::Undo,
+                None,
+                commands_writer,
+            ),
+            None => warn!("Abort requested without open transaction"),
+        }
+    }
+
+    /// Move the pointer back over a step whose undo/redo failed, so it can be retried.
+    /// Steps following the failed one are never dispatched, so the pointer ends right next to it
+    pub fn rollback(&mut self, step: usize, direction: CommandExecuteDirection) {
+        if step >= self.items.len() {
+            return;
+        }
+
+        match direction {
+            CommandExecuteDirection::Undo => self.pointer = self.pointer.max(Some(step)),
+            CommandExecuteDirection::Redo => {
+                let before_step = step.checked_sub(1);
+                self.pointer = self.pointer.min(before_step);
+            }
+        }
+    }
+
+    /// Undo or redo commands one by one until the pointer reaches `target`
+    pub fn move_to(
+        &mut self,
+        target: Option<usize>,
+        commands_writer: &mut EventWriter<UndoRedoCommandEvent>,
+    ) {
+        if matches!(target, Some(index) if index >= self.items.len()) {
+            return;
+        }
+
+        while self.pointer < target {
+            self.redo(commands_writer);
+        }
+        while self.pointer > target {
+            self.undo(commands_writer);
+        }
+    }
+
+    /// Send UndoRedoCommandEvent with CommandExecuteDirection::Redo and increase the pointer
+    pub fn redo(&mut self, commands_writer: &mut EventWriter<UndoRedoCommandEvent>) {
+        self.last_insert = None;
+        let post_redo_index = match self.pointer {
+            Some(ptr) => ptr + 1,
+            None => 0,
+        };
+        if let Some(command) = self.items.get(post_redo_index) {
+            send_undo_redo(
+                command.as_ref(),
+                CommandExecuteDirection::Redo,
+                Some(post_redo_index),
+                commands_writer,
+            );
+            self.pointer = Some(post_redo_index);
+        }
+    }
+
+    /// Send UndoRedoCommandEvent with CommandExecuteDirection::Undo and decrease the pointer
+    pub fn undo(&mut self, commands_writer: &mut EventWriter<UndoRedoCommandEvent>) {
+        self.last_insert = None;
+        let ptr = match self.pointer {
+            Some(ptr) => ptr,
+            None => return,
+        };
+        if let Some(command) = self.items.get(ptr) {
+            send_undo_redo(
+                command.as_ref(),
+                CommandExecuteDirection::Undo,
+                Some(ptr),
+                commands_writer,
+            );
+            self.pointer = if ptr > 0 { Some(ptr - 1) } else { None };
+        }
+    }
+}
+
+pub struct EditorCommandsPlugin;
+impl Plugin for EditorCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExecuteCommandEvent>()
+            .add_event::<UndoRedoCommandEvent>()
+            .add_event::<CommandTransactionEvent>()
+            .add_event::<CommandFailedEvent>()
+            .add_event::<EditorActionEvent>()
+            .add_event::<SaveCommandHistoryEvent>()
+            .init_resource::<CommandQueue>()
+            .init_resource::<CommandRegistry>()
+            .init_resource::<CommandHistoryState>()
+            .insert_resource(CommandHistoryFile {
+                path: editor_directory().join(COMMAND_HISTORY_FILE),
+            })
+            .add_startup_system(load_command_history_system)
+            .add_system_to_stage(CoreStage::PostUpdate, process_commands_system)
+            .add_system(save_action_system)
+            .add_system_to_stage(CoreStage::Last, save_command_history_system)
+            .add_system(undo_redo_system);
+    }
+}
+
+/// Moves CommandExecutedEvents to the CommandQueue.
+/// Runs in PostUpdate, so commands executed during Update land in the same frame transaction.
+/// Commands executed within one frame are ordered by CommandSource
+pub fn process_commands_system(
+    mut queue: ResMut<CommandQueue>,
+    mut commands: EventReader<ExecuteCommandEvent>,
+    mut transactions: EventReader<CommandTransactionEvent>,
+    mut failures: EventReader<CommandFailedEvent>,
+    mut commands_writer: EventWriter<UndoRedoCommandEvent>,
+) {
+    for failure in failures.iter() {
+        error!("{}", failure);
+        if let (Some(step), Some(direction)) = (failure.step, failure.direction) {
+            queue.rollback(step, direction);
+        }
+    }
+
+    let transactions: Vec<&CommandTransactionEvent> = transactions.iter().collect();
+    for transaction in transactions.iter() {
+        if let CommandTransactionEvent::Begin = transaction {
+            queue.begin_transaction();
+        }
+    }
+
+    let mut commands: Vec<&ExecuteCommandEvent> = commands.iter().collect();
+    commands.sort_by_key(|command| (command.source, command.sequence));
+    debug_assert_no_resource_races(&commands);
+    for command in commands {
+        queue.insert(command.consume());
+    }
+
+    for transaction in transactions.iter() {
+        match transaction {
+            CommandTransactionEvent::Begin => (),
+            CommandTransactionEvent::Commit => queue.commit_transaction(),
+            CommandTransactionEvent::Abort => queue.abort_transaction(&mut commands_writer),
+        }
+    }
+}
+
+/// Commands executed in the same frame by different sources must not modify the same resource,
+/// otherwise the final state depends on system scheduling
+fn debug_assert_no_resource_races(commands: &[&ExecuteCommandEvent]) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let mut sources: HashMap<TypeId, CommandSource> = HashMap::default();
+    for command in commands {
+        for resource in command.inner.affected_resources() {
+            let source = *sources.entry(resource).or_insert(command.source);
+            debug_assert!(
+                source == command.source,
+                "{} and {} modified the same resource in one frame",
+                source,
+                command.source
+            );
+        }
+    }
+}
+
+/// System reading Undo and Redo editor actions and moving through the CommandQueue
+pub fn undo_redo_system(
+    mut action_reader: EventReader<EditorActionEvent>,
+    mut commands_writer: EventWriter<UndoRedoCommandEvent>,
+    mut queue: ResMut<CommandQueue>,
+) {
+    for event in action_reader.iter() {
+        match event.action {
+            EditorAction::Undo => queue.undo(&mut commands_writer),
+            EditorAction::Redo => queue.redo(&mut commands_writer),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::commands::dispatch::{AddEditorCommand, CommandBatch, SendCommandEvent};
+    use crate::editor::testing::{CommandTestApp, RecordingCommand};
+    use crate::editor::ui::asset_browser::EnterDirectoryCommand;
+    use std::path::Path;
+
+    fn descriptions(app: &CommandTestApp) -> Vec<String> {
+        app.queue()
+            .items
+            .iter()
+            .map(|item| item.describe())
+            .collect()
+    }
+
+    #[test]
+    fn insert_truncates_redo_chain() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = None;
+        app.send(RecordingCommand::new("a"));
+        app.send(RecordingCommand::new("b"));
+        app.send(RecordingCommand::new("c"));
+        app.undo();
+        app.undo();
+        assert_eq!(app.queue().pointer, Some(0));
+
+        app.send(RecordingCommand::new("d"));
+        assert_eq!(descriptions(&app), ["a", "d"]);
+        assert_eq!(app.queue().pointer, Some(1));
+    }
+
+    #[test]
+    fn insert_after_undoing_everything_clears_chain() {
+        let mut app = CommandTestApp::new();
+        app.send(RecordingCommand::new("a"));
+        app.undo();
+        assert_eq!(app.queue().pointer, None);
+
+        app.send(RecordingCommand::new("b"));
+        assert_eq!(descriptions(&app), ["b"]);
+        assert_eq!(app.queue().pointer, Some(0));
+    }
+
+    #[test]
+    fn undo_and_redo_stop_at_chain_ends() {
+        let mut app = CommandTestApp::new();
+        app.undo();
+        app.redo();
+        assert_eq!(app.queue().pointer, None);
+
+        app.send(RecordingCommand::new("a"));
+        app.redo();
+        assert_eq!(app.queue().pointer, Some(0));
+        app.undo();
+        app.undo();
+        assert_eq!(app.queue().pointer, None);
+        app.redo();
+        assert_eq!(app.queue().pointer, Some(0));
+        assert_eq!(app.log(), ["apply a", "revert a", "apply a"]);
+    }
+
+    #[test]
+    fn mergeable_commands_collapse_within_window() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = Some(Duration::from_secs(60));
+        for name in ["a", "b", "c"] {
+            app.send(RecordingCommand {
+                mergeable: true,
+                ..RecordingCommand::new(name)
+            });
+        }
+        assert_eq!(descriptions(&app), ["a+b+c"]);
+
+        app.queue_mut().merge_window = None;
+        app.send(RecordingCommand {
+            mergeable: true,
+            ..RecordingCommand::new("d")
+        });
+        assert_eq!(descriptions(&app), ["a+b+c", "d"]);
+    }
+
+    #[test]
+    fn directories_entered_at_double_click_pace_are_merged() {
+        let mut queue = CommandQueue::default();
+        let start = Instant::now();
+        let enter = |from: &str, to: &str| {
+            Box::new(EnterDirectoryCommand {
+                previous_selected_directory: from.into(),
+                new_selected_directory: to.into(),
+            })
+        };
+        queue.insert_at(enter("/assets", "/assets/a"), start);
+        queue.insert_at(
+            enter("/assets/a", "/assets/a/b"),
+            start + Duration::from_millis(900),
+        );
+        queue.insert_at(
+            enter("/assets/a/b", "/assets/a/b/c"),
+            start + Duration::from_millis(2100),
+        );
+        let merged = queue.items[0]
+            .as_any()
+            .downcast_ref::<EnterDirectoryCommand>();
+        assert_eq!(queue.items.len(), 1);
+        assert_eq!(
+            merged.map(|command| command.new_selected_directory.as_path()),
+            Some(Path::new("/assets/a/b/c"))
+        );
+
+        queue.insert_at(
+            enter("/assets/a/b/c", "/assets"),
+            start + Duration::from_secs(10),
+        );
+        assert_eq!(queue.items.len(), 2);
+    }
+
+    #[test]
+    fn limits_evict_oldest_commands() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = None;
+        app.queue_mut().limits = CommandHistoryLimits {
+            max_commands: Some(2),
+            max_bytes: None,
+        };
+        for name in ["a", "b", "c"] {
+            app.send(RecordingCommand::new(name));
+        }
+        assert_eq!(descriptions(&app), ["b", "c"]);
+        assert_eq!(app.queue().pointer, Some(1));
+
+        app.undo();
+        app.undo();
+        app.undo();
+        assert_eq!(app.queue().pointer, None);
+        assert_eq!(app.log().last().unwrap(), "revert b");
+    }
+
+    #[test]
+    fn transaction_is_undone_in_reverse_order() {
+        let mut app = CommandTestApp::new();
+        app.app.world.send_event(CommandTransactionEvent::Begin);
+        app.send(RecordingCommand::new("a"));
+        app.send(RecordingCommand::new("b"));
+        app.app.world.send_event(CommandTransactionEvent::Commit);
+        app.update();
+        assert_eq!(app.queue().items.len(), 1);
+
+        app.undo();
+        app.redo();
+        assert_eq!(
+            app.log(),
+            ["apply a", "apply b", "revert b", "revert a", "apply a", "apply b"]
+        );
+    }
+
+    #[test]
+    fn transaction_sent_within_one_frame_is_one_step() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = None;
+        app.app.world.send_event(CommandTransactionEvent::Begin);
+        app.app
+            .world
+            .send_event(SendCommandEvent::new(RecordingCommand::new("a")));
+        app.app
+            .world
+            .send_event(SendCommandEvent::new(RecordingCommand::new("b")));
+        app.app.world.send_event(CommandTransactionEvent::Commit);
+        app.app.update();
+        assert_eq!(descriptions(&app), ["a (+1 more)"]);
+        assert!(!app.queue().in_transaction());
+    }
+
+    #[test]
+    fn aborted_transaction_is_reverted_and_dropped() {
+        let mut app = CommandTestApp::new();
+        app.app.world.send_event(CommandTransactionEvent::Begin);
+        app.send(RecordingCommand::new("a"));
+        app.app.world.send_event(CommandTransactionEvent::Abort);
+        // Aborted commands are reverted by the dispatcher in the following frame
+        app.update();
+        app.update();
+        assert!(app.queue().items.is_empty());
+        assert_eq!(app.log(), ["apply a", "revert a"]);
+    }
+
+    #[test]
+    fn failed_execution_is_not_recorded() {
+        let mut app = CommandTestApp::new();
+        app.send(RecordingCommand {
+            fail: true,
+            ..RecordingCommand::new("a")
+        });
+        assert!(app.queue().items.is_empty());
+        assert_eq!(app.drain_failures().len(), 1);
+    }
+
+    #[test]
+    fn failed_undo_keeps_pointer() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().insert(Box::new(RecordingCommand {
+            fail: true,
+            ..RecordingCommand::new("a")
+        }));
+        app.undo();
+        assert_eq!(app.queue().pointer, Some(0));
+        assert_eq!(
+            app.drain_failures(),
+            ["Undo of \"a\" failed: revert a failed"]
+        );
+    }
+
+    #[test]
+    fn move_to_reaches_target_in_one_go() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = None;
+        for name in ["a", "b", "c"] {
+            app.send(RecordingCommand::new(name));
+        }
+
+        app.move_to(None);
+        assert_eq!(app.queue().pointer, None);
+        app.move_to(Some(1));
+        assert_eq!(app.queue().pointer, Some(1));
+        assert_eq!(
+            &app.log()[3..],
+            ["revert c", "revert b", "revert a", "apply a", "apply b"]
+        );
+    }
+
+    #[test]
+    fn move_to_stops_at_failed_step() {
+        let mut app = CommandTestApp::new();
+        for command in [
+            RecordingCommand::new("a"),
+            RecordingCommand {
+                fail: true,
+                ..RecordingCommand::new("b")
+            },
+            RecordingCommand::new("c"),
+        ] {
+            app.queue_mut().insert(Box::new(command));
+        }
+
+        app.move_to(None);
+        assert_eq!(app.log(), ["revert c"]);
+        assert_eq!(app.queue().pointer, Some(1));
+        assert_eq!(app.drain_failures().len(), 1);
+    }
+
+    #[test]
+    fn failed_compound_child_rolls_back_its_siblings() {
+        let mut app = CommandTestApp::new();
+        let failing = RecordingCommand {
+            fail: true,
+            ..RecordingCommand::new("b")
+        };
+        app.queue_mut().insert(Box::new(CompoundCommand {
+            children: vec![
+                Box::new(RecordingCommand::new("a")),
+                Box::new(failing),
+                Box::new(RecordingCommand::new("c")),
+            ],
+        }));
+
+        app.undo();
+        assert_eq!(app.log(), ["revert c", "apply c"]);
+        assert_eq!(app.queue().pointer, Some(0));
+    }
+
+    #[test]
+    fn move_to_undoes_mixed_types_in_order() {
+        let mut app = CommandTestApp::new();
+        app.app
+            .add_editor_command::<CommandBatch<RecordingCommand>>();
+        app.queue_mut().merge_window = None;
+        app.send(CommandBatch {
+            commands: vec![RecordingCommand::new("x")],
+        });
+        app.send(RecordingCommand::new("y"));
+
+        app.move_to(None);
+        app.move_to(Some(1));
+        assert_eq!(
+            &app.log()[2..],
+            ["revert y", "revert x", "apply x", "apply y"]
+        );
+    }
+
+    #[test]
+    fn commands_from_one_frame_are_ordered_by_source() {
+        let mut app = CommandTestApp::new();
+        app.queue_mut().merge_window = None;
+        for (name, source) in [("b1", "b"), ("a1", "a"), ("b2", "b")] {
+            let command = Box::new(RecordingCommand::new(name));
+            app.app
+                .world
+                .send_event(ExecuteCommandEvent::new(command, source));
+        }
+        app.update();
+        assert_eq!(descriptions(&app), ["a1", "b1", "b2"]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "modified the same resource")]
+    fn racing_sources_are_detected() {
+        let events: Vec<ExecuteCommandEvent> = ["a", "b"]
+            .into_iter()
+            .map(|source| {
+                let command = Box::new(EnterDirectoryCommand {
+                    previous_selected_directory: "a".into(),
+                    new_selected_directory: "b".into(),
+                });
+                ExecuteCommandEvent::new(command, source)
+            })
+            .collect();
+        debug_assert_no_resource_races(&events.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn failed_batch_is_rolled_back() {
+        let mut app = CommandTestApp::new();
+        app.app
+            .add_editor_command::<CommandBatch<RecordingCommand>>();
+        let failing = RecordingCommand {
+            fail: true,
+            ..RecordingCommand::new("c")
+        };
+        app.send(CommandBatch {
+            commands: vec![
+                RecordingCommand::new("a"),
+                RecordingCommand::new("b"),
+                failing,
+            ],
+        });
+        assert_eq!(app.log(), ["apply a", "apply b", "revert b", "revert a"]);
+        assert!(app.queue().items.is_empty());
+        assert_eq!(app.drain_failures().len(), 1);
+
+        app.send(CommandBatch {
+            commands: vec![RecordingCommand::new("d"), RecordingCommand::new("e")],
+        });
+        assert_eq!(descriptions(&app), ["d (+1 more)"]);
+        app.undo();
+        assert_eq!(app.log()[6..], ["revert e", "revert d"]);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::editor::commands::{Command, CommandAny, CommandQueue, CompoundCommand};
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Name of the history file stored in the editor directory of the project
+pub const COMMAND_HISTORY_FILE: &str = "command_history.ron";
+
+/// Time since the last CommandQueue change after which the history is saved
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Functions used for (de)serializing single registered command type
+struct CommandRegistration {
+    name: &'static str,
+    serialize: fn(&dyn CommandAny) -> Option<String>,
+    deserialize: fn(&str) -> Option<Box<dyn CommandAny>>,
+}
+
+/// Registry of commands that can be persisted. Commands are identified by stable names, so
+/// renaming or moving the type doesn't invalidate already saved history
+#[derive(Default)]
+pub struct CommandRegistry {
+    by_type: HashMap<TypeId, CommandRegistration>,
+    by_name: HashMap<&'static str, TypeId>,
+}
+
+impl CommandRegistry {
+    /// Register command type under given stable name
+    pub fn register<T>(&mut self, name: &'static str)
+    where
+        T: Command + Serialize + DeserializeOwned,
+    {
+        let registration = CommandRegistration {
+            name,
+            serialize: |command| {
+                let command: &T = command.as_any().downcast_ref()?;
+                ron::to_string(command).ok()
+            },
+            deserialize: |data| {
+                let command: T = ron::from_str(data).ok()?;
+                Some(Box::new(command))
+            },
+        };
+        self.by_type.insert(TypeId::of::<T>(), registration);
+        self.by_name.insert(name, TypeId::of::<T>());
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// Serialize command, returns None if it (or all of its children) is not registered
+    pub fn serialize(&self, command: &dyn CommandAny) -> Option<PersistedCommand> {
+        if let Some(compound) = command.as_any().downcast_ref::<CompoundCommand>() {
+            let children: Vec<PersistedCommand> = compound
+                .children
+                .iter()
+                .filter_map(|child| self.serialize(child.as_ref()))
+                .collect();
+            return (!children.is_empty()).then_some(PersistedCommand::Compound(children));
+        }
+
+        let registration = self.by_type.get(&command.command_type())?;
+        Some(PersistedCommand::Single {
+            name: registration.name.into(),
+            data: (registration.serialize)(command)?,
+        })
+    }
+
+    /// Deserialize command, returns None if its type is no longer registered
+    pub fn deserialize(&self, persisted: &PersistedCommand) -> Option<Box<dyn CommandAny>> {
+        match persisted {
+            PersistedCommand::Single { name, data } => {
+                let type_id = self.by_name.get(name.as_str())?;
+                (self.by_type[type_id].deserialize)(data)
+            }
+            PersistedCommand::Compound(children) => {
+                let children: Vec<Box<dyn CommandAny>> = children
+                    .iter()
+                    .filter_map(|child| self.deserialize(child))
+                    .collect();
+                (!children.is_empty())
+                    .then(|| Box::new(CompoundCommand { children }) as Box<dyn CommandAny>)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PersistedCommand {
+    Single { name: String, data: String },
+    Compound(Vec<PersistedCommand>),
+}
+
+/// Serializable form of the CommandQueue
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PersistedHistory {
+    pub pointer: Option<usize>,
+    pub items: Vec<PersistedCommand>,
+}
+
+impl CommandQueue {
+    /// Convert the chain to serializable form. Commands that are not registered are skipped
+    pub fn persist(&self, registry: &CommandRegistry) -> PersistedHistory {
+        let mut history = PersistedHistory::default();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(persisted) = registry.serialize(item.as_ref()) {
+                history.items.push(persisted);
+                if self.pointer >= Some(index) {
+                    history.pointer = Some(history.items.len() - 1);
+                }
+            }
+        }
+        history
+    }
+
+    /// Replace the chain with persisted one. Commands whose type is no longer registered are
+    /// skipped and the pointer is adjusted accordingly
+    pub fn restore(&mut self, history: &PersistedHistory, registry: &CommandRegistry) {
+        self.items.clear();
+        self.pointer = None;
+        for (index, persisted) in history.items.iter().enumerate() {
+            if let Some(command) = registry.deserialize(persisted) {
+                self.items.push(command);
+                if history.pointer >= Some(index) {
+                    self.pointer = Some(self.items.len() - 1);
+                }
+            }
+        }
+    }
+}
+
+/// App extension used by plugins for registering their persistable commands
+pub trait RegisterEditorCommand {
+    fn register_editor_command<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Command + Serialize + DeserializeOwned;
+}
+
+impl RegisterEditorCommand for App {
+    fn register_editor_command<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Command + Serialize + DeserializeOwned,
+    {
+        self.world
+            .get_resource_or_insert_with(CommandRegistry::default)
+            .register::<T>(name);
+        self
+    }
+}
+
+/// Location of the persisted history. History is neither loaded nor saved without this resource
+pub struct CommandHistoryFile {
+    pub path: PathBuf,
+}
+
+/// State of the persisted history
+#[derive(Default)]
+pub struct CommandHistoryState {
+    /// History of the previous session was loaded. False when there was no history file,
+    /// or it couldn't be read
+    pub restored: bool,
+    /// History as last loaded or saved, the same history is not written again
+    saved: Option<String>,
+}
+
+/// Save the history right away instead of waiting for SAVE_DELAY, e.g. after a command
+/// whose undo would be impossible without the history
+pub struct SaveCommandHistoryEvent;
+
+/// Restore the CommandQueue saved by the previous editor session
+pub fn load_command_history_system(
+    history_file: Option<Res<CommandHistoryFile>>,
+    registry: Res<CommandRegistry>,
+    mut queue: ResMut<CommandQueue>,
+    mut state: ResMut<CommandHistoryState>,
+) {
+    let history_file = match history_file {
+        Some(history_file) => history_file,
+        None => return,
+    };
+
+    if history_file.path.exists() {
+        let history = std::fs::read_to_string(&history_file.path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                ron::from_str::<PersistedHistory>(&content).map_err(|e| e.to_string())
+            });
+        match history {
+            Ok(history) => {
+                queue.restore(&history, &registry);
+                state.restored = true;
+            }
+            Err(e) => error!("Failed to load {:?}: {}", history_file.path, e),
+        }
+    }
+    state.saved = serialize_history(&queue, &registry).ok();
+}
+
+fn serialize_history(queue: &CommandQueue, registry: &CommandRegistry) -> Result<String, String> {
+    let history = queue.persist(registry);
+    ron::ser::to_string_pretty(&history, Default::default()).map_err(|e| e.to_string())
+}
+
+/// Request saving the history on the Save editor action
+pub fn save_action_system(
+    mut action_reader: EventReader<EditorActionEvent>,
+    mut save_writer: EventWriter<SaveCommandHistoryEvent>,
+) {
+    for event in action_reader.iter() {
+        if let EditorAction::Save = event.action {
+            save_writer.send(SaveCommandHistoryEvent);
+        }
+    }
+}
+
+/// Save the CommandQueue once it stops changing for SAVE_DELAY, and right away when requested
+/// by SaveCommandHistoryEvent or when the app is about to exit with unsaved changes. History that didn't change since it was loaded is not written
+#[allow(clippy::too_many_arguments)]
+pub fn save_command_history_system(
+    history_file: Option<Res<CommandHistoryFile>>,
+    registry: Res<CommandRegistry>,
+    queue: Res<CommandQueue>,
+    mut state: ResMut<CommandHistoryState>,
+    time: Res<Time>,
+    mut save_at: Local<Option<f64>>,
+    mut exit_reader: EventReader<AppExit>,
+    mut save_reader: EventReader<SaveCommandHistoryEvent>,
+) {
+    let exiting = exit_reader.iter().count() > 0;
+    let requested = save_reader.iter().count() > 0;
+    let now = time.seconds_since_startup();
+    if queue.is_changed() {
+        *save_at = Some(now + SAVE_DELAY.as_secs_f64());
+    }
+    let history_file = match (history_file, *save_at) {
+        (Some(history_file), Some(at)) if exiting || requested || at <= now => history_file,
+        _ => return,
+    };
+    *save_at = None;
+
+    let content = match serialize_history(&queue, &registry) {
+        Ok(content) if state.saved.as_ref() == Some(&content) => return,
+        content => content,
+    };
+    let saved = content.and_then(|content| {
+        if let Some(parent) = history_file.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&history_file.path, &content).map_err(|e| e.to_string())?;
+        state.saved = Some(content);
+        Ok(())
+    });
+    if let Err(e) = saved {
+        error!("Failed to save {:?}: {}", history_file.path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::testing::{CommandTestApp, RecordingCommand};
+    use crate::editor::ui::asset_browser::EnterDirectoryCommand;
+
+    fn enter_directory(from: &str, to: &str) -> Box<dyn CommandAny> {
+        Box::new(EnterDirectoryCommand {
+            previous_selected_directory: from.into(),
+            new_selected_directory: to.into(),
+        })
+    }
+
+    #[test]
+    fn unregistered_commands_are_skipped_on_restore() {
+        let mut registry = CommandRegistry::default();
+        registry.register::<EnterDirectoryCommand>("bevytor::EnterDirectoryCommand");
+        let queue = CommandQueue {
+            items: vec![
+                enter_directory("a", "b"),
+                enter_directory("b", "c"),
+                enter_directory("c", "d"),
+            ],
+            pointer: Some(1),
+            ..Default::default()
+        };
+        let mut history = queue.persist(&registry);
+        let serialized = ron::to_string(&history).unwrap();
+        history = ron::from_str(&serialized).unwrap();
+
+        let mut registry = CommandRegistry::default();
+        registry.register::<EnterDirectoryCommand>("bevytor::RenamedCommand");
+        let mut restored = CommandQueue::default();
+        restored.restore(&history, &registry);
+        assert!(restored.items.is_empty());
+        assert_eq!(restored.pointer, None);
+
+        registry.register::<EnterDirectoryCommand>("bevytor::EnterDirectoryCommand");
+        restored.restore(&history, &registry);
+        assert_eq!(restored.items.len(), 3);
+        assert_eq!(restored.pointer, Some(1));
+        assert_eq!(restored.items[2].describe(), "Enter directory d");
+    }
+
+    fn app_with_history_file() -> (CommandTestApp, PathBuf) {
+        let mut app = CommandTestApp::new();
+        let path = app.path(COMMAND_HISTORY_FILE);
+        app.app
+            .insert_resource(CommandHistoryFile { path: path.clone() });
+        (app, path)
+    }
+
+    #[test]
+    fn history_is_saved_after_delay() {
+        let (mut app, path) = app_with_history_file();
+        app.send(RecordingCommand::new("a"));
+        app.advance_time(SAVE_DELAY / 2);
+        app.send(RecordingCommand::new("b"));
+        app.advance_time(SAVE_DELAY / 2);
+        app.update();
+        assert!(!path.exists());
+
+        app.advance_time(SAVE_DELAY / 2);
+        app.update();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn history_is_saved_on_exit() {
+        let (mut app, path) = app_with_history_file();
+        app.send(RecordingCommand::new("a"));
+        assert!(!path.exists());
+
+        app.app.world.send_event(AppExit);
+        app.update();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn history_is_saved_on_save_action() {
+        let (mut app, path) = app_with_history_file();
+        app.send(RecordingCommand::new("a"));
+        app.app.world.send_event(EditorActionEvent {
+            action: EditorAction::Save,
+        });
+        app.update();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn unchanged_history_is_not_saved() {
+        let (mut app, path) = app_with_history_file();
+        app.update();
+        app.advance_time(SAVE_DELAY * 2);
+        app.update();
+        app.app.world.send_event(AppExit);
+        app.update();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Named editor actions. Systems should react to EditorActionEvent instead of raw keyboard input
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum EditorAction {
+    Undo,
+    Redo,
+    NewScene,
+    /// Save the editor session, i.e. the undo history
+    Save,
+    Delete,
+    Rename,
+    /// Asset browser directory history
+    NavigateBack,
+    NavigateForward,
+    /// Enter parent directory in the asset browser
+    NavigateUp,
+}
+
+/// Key combination triggering an action. Modifiers have to match exactly, so Ctrl+Z and
+/// Ctrl+Shift+Z are different chords
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    /// Platform "command" modifier: Cmd on macOS, Ctrl everywhere else
+    #[serde(default)]
+    pub command: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            command: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    /// Chord with the platform command modifier
+    pub fn command(key: KeyCode) -> Self {
+        Self {
+            command: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn with_shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+
+    pub fn with_alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    /// Returns true if the chord key was just pressed with exactly the chord modifiers held
+    pub fn just_pressed(&self, keyboard: &Input<KeyCode>) -> bool {
+        let command_keys = if cfg!(target_os = "macos") {
+            [KeyCode::LWin, KeyCode::RWin]
+        } else {
+            [KeyCode::LControl, KeyCode::RControl]
+        };
+        keyboard.just_pressed(self.key)
+            && keyboard.any_pressed(command_keys) == self.command
+            && keyboard.any_pressed([KeyCode::LShift, KeyCode::RShift]) == self.shift
+            && keyboard.any_pressed([KeyCode::LAlt, KeyCode::RAlt]) == self.alt
+    }
+}
+
+/// Resource mapping editor actions to key chords
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditorKeymap {
+    pub bindings: HashMap<EditorAction, Vec<KeyChord>>,
+}
+
+impl Default for EditorKeymap {
+    fn default() -> Self {
+        let bindings = [
+            (EditorAction::Undo, vec![KeyChord::command(KeyCode::Z)]),
+            (
+                EditorAction::Redo,
+                vec![
+                    KeyChord::command(KeyCode::Y),
+                    KeyChord::command(KeyCode::Z).with_shift(),
+                ],
+            ),
+            (EditorAction::NewScene, vec![KeyChord::command(KeyCode::N)]),
+            (EditorAction::Save, vec![KeyChord::command(KeyCode::S)]),
+            (EditorAction::Delete, vec![KeyChord::new(KeyCode::Delete)]),
+            (EditorAction::Rename, vec![KeyChord::new(KeyCode::F2)]),
+            (
+                EditorAction::NavigateBack,
+                vec![KeyChord::new(KeyCode::Left).with_alt()],
+            ),
+            (
+                EditorAction::NavigateForward,
+                vec![KeyChord::new(KeyCode::Right).with_alt()],
+            ),
+            (
+                EditorAction::NavigateUp,
+                vec![KeyChord::new(KeyCode::Up).with_alt()],
+            ),
+        ];
+
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl EditorKeymap {
+    /// Load keymap from RON file. Actions bound in the file replace the default bindings,
+    /// all the other actions keep their defaults. Missing or invalid file results in defaults
+    pub fn load_or_default(path: &Path) -> Self {
+        let mut keymap = Self::default();
+        if !path.exists() {
+            return keymap;
+        }
+
+        let loaded = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| ron::from_str::<EditorKeymap>(&content).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(loaded) => keymap.bindings.extend(loaded.bindings),
+            Err(e) => error!("Failed to load keymap {:?}: {}", path, e),
+        }
+        keymap
+    }
+
+    /// Actions triggered by keys pressed in this frame
+    pub fn triggered<'a>(
+        &'a self,
+        keyboard: &'a Input<KeyCode>,
+    ) -> impl Iterator<Item = EditorAction> + 'a {
+        self.bindings
+            .iter()
+            .filter(|(_, chords)| chords.iter().any(|chord| chord.just_pressed(keyboard)))
+            .map(|(action, _)| *action)
+    }
+}
+
+/// Sent whenever key chord bound to an action was pressed
+#[derive(Debug, Clone, Copy)]
+pub struct EditorActionEvent {
+    pub action: EditorAction,
+}
+
+pub struct KeymapPlugin;
+impl Plugin for KeymapPlugin {
+    fn build(&self, app: &mut App) {
+        let keymap = EditorKeymap::load_or_default(&super::editor_directory().join(KEYMAP_FILE));
+        app.insert_resource(keymap)
+            .add_event::<EditorActionEvent>()
+            .add_system(keymap_system);
+    }
+}
+
+/// Name of the keymap file stored in the editor directory of the project
+pub const KEYMAP_FILE: &str = "keymap.ron";
+
+/// Translate keyboard input into EditorActionEvents. Nothing is sent while egui is using
+/// the keyboard, so undo in a text field doesn't trigger editor undo
+pub fn keymap_system(
+    keyboard: Res<Input<KeyCode>>,
+    keymap: Res<EditorKeymap>,
+    egui_context: Option<ResMut<EguiContext>>,
+    mut action_writer: EventWriter<EditorActionEvent>,
+) {
+    if let Some(mut egui_context) = egui_context {
+        if egui_context.ctx_mut().wants_keyboard_input() {
+            return;
+        }
+    }
+
+    for action in keymap.triggered(&keyboard) {
+        action_writer.send(EditorActionEvent { action });
+    }
+}
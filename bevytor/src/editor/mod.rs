@@ -1,21 +1,32 @@
-use crate::editor::assets::asset_loader::*;
-use crate::editor::commands::*;
-use crate::editor::scene::{
-    create_scene_system, CreateSceneCommand, EditorScenePlugin, SelectedScene,
-};
-use crate::editor::ui::asset_browser::*;
+use crate::editor::assets::dependencies::AssetDependencyPlugin;
+use crate::editor::assets::import::ImportSettingsPlugin;
+use crate::editor::assets::watcher::AssetWatcherPlugin;
+use crate::editor::commands::dispatch::CommandWriter;
+use crate::editor::commands::EditorCommandsPlugin;
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use crate::editor::scene::{CreateSceneCommand, EditorScenePlugin};
 use assets::asset_loader::AssetLoaderPlugin;
 use bevy::app::{Plugin, PluginGroup, PluginGroupBuilder};
 use bevy::ecs::schedule::ShouldRun;
 use bevy::prelude::*;
-use bevy_egui::egui::{menu, SelectableLabel, TopBottomPanel};
+use bevy_egui::egui::{menu, TopBottomPanel};
 use bevy_egui::{EguiContext, EguiPlugin};
+use keymap::KeymapPlugin;
 use std::env;
+use std::path::PathBuf;
 use ui::asset_browser::AssetBrowserPlugin;
+use ui::dependencies::DependencyViewPlugin;
+use ui::history::{HistoryPanelContext, HistoryPanelPlugin};
+use ui::notifications::NotificationsPlugin;
+use ui::preview::AssetPreviewPlugin;
+use ui::scan_progress::ScanProgressPlugin;
 
 pub mod assets;
 pub mod commands;
+pub mod keymap;
 pub mod scene;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod ui;
 
 fn run_if_post_initializing_assets(editor_state: Res<EditorStateLabel>) -> ShouldRun {
@@ -26,6 +37,17 @@ fn run_if_post_initializing_assets(editor_state: Res<EditorStateLabel>) -> Shoul
     }
 }
 
+/// Directory containing editor files of the project (keymap, history etc.)
+/// TODO: Game directory should be editable per editor project
+pub fn editor_directory() -> PathBuf {
+    const GAME_DIRECTORY_NAME: &str = "game";
+    const EDITOR_DIRECTORY_NAME: &str = ".bevytor";
+    env::current_dir()
+        .unwrap()
+        .join(GAME_DIRECTORY_NAME)
+        .join(EDITOR_DIRECTORY_NAME)
+}
+
 /// Label used for labeling editor dependent systems
 /// EditorStateLabel::InitializingAssets - At this stage both editor and game assets are being
 ///                                         initialized and are not available
@@ -43,8 +65,17 @@ impl PluginGroup for EditorPlugins {
         group
             .add(EguiPlugin)
             .add(AssetLoaderPlugin)
+            .add(AssetWatcherPlugin)
+            .add(ImportSettingsPlugin)
+            .add(AssetDependencyPlugin)
+            .add(KeymapPlugin)
             .add(EditorCommandsPlugin)
             .add(AssetBrowserPlugin)
+            .add(HistoryPanelPlugin)
+            .add(NotificationsPlugin)
+            .add(AssetPreviewPlugin)
+            .add(DependencyViewPlugin)
+            .add(ScanProgressPlugin)
             .add(EditorScenePlugin);
     }
 }
@@ -63,53 +94,13 @@ impl Default for ShowCreateSceneWidgetContext {
     }
 }
 
-/// Plugin segregation was faulty, lets bind everything in one plugin
+/// Complete editor, the EditorPlugins group together with the menu bar
 pub struct EditorPlugin;
 impl Plugin for EditorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(EguiPlugin);
-        // Setup AssetLoaderPlugin
-        //
-        // It is really important to prepare AssetDirectory resource here and not in startup system,
-        // as resources inserted through Commands are first available in the next frame
-        const ASSET_DIRECTORY_NAME: &str = "assets";
-        // TODO: Game asset directory should be editable per editor project
-        const GAME_DIRECTORY_NAME: &str = "game";
-        let asset_dir = env::current_dir()
-            .unwrap()
-            .join(GAME_DIRECTORY_NAME)
-            .join(ASSET_DIRECTORY_NAME);
-        let root = AssetDirectory::new(asset_dir.clone());
-
-        app.insert_resource(root)
-            .insert_resource(EditorAssets::default())
+        app.add_plugins(EditorPlugins)
             .insert_resource(ShowCreateSceneWidgetContext::default())
-            .add_startup_system(load_editor_assets_system)
-            .add_startup_system(load_assets_system.after(load_editor_assets_system))
             .add_system(show_menu_bar);
-
-        // Setup EditorCommandsPlugin
-        app.add_event::<ExecuteCommandEvent>()
-            .add_event::<UndoRedoCommandEvent>()
-            .insert_resource(CommandQueue {
-                items: Vec::new(),
-                pointer: None,
-            })
-            .add_system(process_commands_system)
-            .add_system(undo_redo_system);
-
-        // Setup AssetBrowserPlugin
-        app.add_event::<EnterDirectoryCommand>()
-            .insert_resource(AssetBrowserSettings::default())
-            .insert_resource(SelectedDirectory::default())
-            .add_startup_system(selection_setup.after(load_assets_system))
-            .add_system(asset_browser_system)
-            .add_system(select_directory_system);
-
-        // Setup ScenePickerPlugin
-        app.insert_resource(SelectedScene::default())
-            .add_event::<CreateSceneCommand>()
-            .add_system(create_scene_system);
     }
 
     fn name(&self) -> &str {
@@ -120,9 +111,17 @@ impl Plugin for EditorPlugin {
 fn show_menu_bar(
     mut egui_context: ResMut<EguiContext>,
     mut show_create_scene_widget: ResMut<ShowCreateSceneWidgetContext>,
-    mut create_scene_command_writer: EventWriter<CreateSceneCommand>,
+    mut history_panel: ResMut<HistoryPanelContext>,
+    mut command_writer: CommandWriter,
+    mut action_reader: EventReader<EditorActionEvent>,
 ) {
-    TopBottomPanel::top("MenuBar").show(&egui_context.ctx_mut(), |ui| {
+    for event in action_reader.iter() {
+        if let EditorAction::NewScene = event.action {
+            show_create_scene_widget.show_widget = true;
+        }
+    }
+
+    TopBottomPanel::top("MenuBar").show(egui_context.ctx_mut(), |ui| {
         menu::bar(ui, |ui| {
             menu::menu_button(ui, "File", |ui| {
                 if !show_create_scene_widget.show_widget {
@@ -131,6 +130,9 @@ fn show_menu_bar(
                     });
                 };
             });
+            menu::menu_button(ui, "View", |ui| {
+                ui.toggle_value(&mut history_panel.show_panel, "History");
+            });
         });
     });
 
@@ -138,7 +140,7 @@ fn show_menu_bar(
         if let Some(command) =
             CreateSceneCommand::widget(&mut egui_context, &mut show_create_scene_widget)
         {
-            create_scene_command_writer.send(command);
+            command_writer.send(command);
         }
     }
 }
@@ -1,15 +1,27 @@
-use std::{any::TypeId, fs::File, io::Write};
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+};
 
 use crate::editor::assets::asset_loader::SceneAssetDescriptor;
-use bevy::{prelude::*, reflect::TypeRegistry, tasks::IoTaskPool};
+use crate::editor::assets::meta::{AssetGuidIndex, AssetReference};
+use bevy::{
+    asset::AssetPath, hierarchy::despawn_with_children_recursive, prelude::*,
+    reflect::TypeRegistry, utils::Uuid,
+};
 use bevy_egui::{
-    egui::{self, Align2, Ui, Window},
+    egui::{Align2, Window},
     EguiContext,
 };
+use serde::{Deserialize, Serialize};
 
 use super::{
     assets::asset_loader::AssetDescriptor,
-    commands::{Command, CommandAny, UndoRedoCommandEvent, ExecuteCommandEvent, CommandExecuteDirection},
+    commands::{
+        dispatch::{AddEditorCommand, ApplyCommand},
+        persistence::RegisterEditorCommand,
+        Command, CommandAny, CommandError,
+    },
     ShowCreateSceneWidgetContext,
 };
 
@@ -17,8 +29,11 @@ pub struct EditorScenePlugin;
 impl Plugin for EditorScenePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SelectedScene::default())
-            .add_system(open_scene_system)
-            .add_system(create_scene_system);
+            .add_editor_command::<CreateSceneCommand>()
+            .register_editor_command::<CreateSceneCommand>("bevytor::CreateSceneCommand")
+            .add_editor_command::<InstantiateModelCommand>()
+            .add_event::<OpenSceneCommand>()
+            .add_system(open_scene_system);
     }
 }
 
@@ -27,7 +42,7 @@ pub struct SelectedScene {
     pub descriptor: Option<SceneAssetDescriptor>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CreateSceneCommand {
     pub scene: Option<SceneAssetDescriptor>,
 }
@@ -51,11 +66,11 @@ impl CreateSceneCommand {
                 });
                 ui.horizontal(|ui| {
                     if ui.button("Create").clicked() {
+                        let name = scene_file_name(&widget_context.scene_name);
                         create_scene_command = Some(CreateSceneCommand {
                             scene: Some(SceneAssetDescriptor {
-                                name: widget_context.scene_name.as_str().into(),
-                                path: format!("game/assets/scenes/{}", widget_context.scene_name)
-                                    .into(),
+                                path: format!("game/assets/scenes/{}", name).into(),
+                                name: name.into(),
                             }),
                         });
                         is_open = false;
@@ -65,7 +80,7 @@ impl CreateSceneCommand {
                     }
                 })
             });
-            if is_open == false {
+            if !is_open {
                 widget_context.show_widget = false;
             }
         };
@@ -74,6 +89,15 @@ impl CreateSceneCommand {
     }
 }
 
+/// Scenes are recognised by the ".scn.ron" extension, plain ".ron" files are text assets
+fn scene_file_name(name: &str) -> String {
+    let name = name.trim();
+    if name.ends_with(".scn.ron") {
+        return name.into();
+    }
+    format!("{}.scn.ron", name.strip_suffix(".ron").unwrap_or(name))
+}
+
 impl Command for CreateSceneCommand {
     fn recreate(&self) -> Box<dyn CommandAny> {
         Box::new(self.clone())
@@ -82,84 +106,140 @@ impl Command for CreateSceneCommand {
     fn command_type(&self) -> TypeId {
         TypeId::of::<CreateSceneCommand>()
     }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .scene
+                .as_ref()
+                .map_or(0, |scene| scene.name.len() + scene.path.as_os_str().len())
+    }
+
+    fn describe(&self) -> String {
+        match &self.scene {
+            Some(scene) => format!("Create scene {}", scene.get_name()),
+            None => "Create scene".into(),
+        }
+    }
 }
 
-// Returns true if scene was saved
-fn create_and_save_scene(
-    path: String,
-    parent: String,
-) -> bool {
+/// Create empty scene file. Fails if the file already exists, so nothing gets overwritten
+fn create_and_save_scene(path: &Path) -> Result<(), CommandError> {
+    if path.exists() {
+        return Err(format!("{:?} already exists", path).into());
+    }
+
     let world = World::new();
     let type_registry = TypeRegistry::default();
     let scene = DynamicScene::from_world(&world, &type_registry);
-    let serialized_scene = scene.serialize_ron(&type_registry).unwrap();
-    if !std::path::Path::new(&path).exists() {
-        false
-    } else {
-        std::fs::create_dir_all(parent).unwrap();
-        File::create(path).and_then(|mut file| file.write(serialized_scene.as_bytes())).is_ok()
-    }
-}
-
-pub fn create_scene_system(
-    mut create_scene_command_reader: EventReader<CreateSceneCommand>,
-    mut undo_redo_command_reader: EventReader<UndoRedoCommandEvent>,
-    mut execute_command_writer: EventWriter<ExecuteCommandEvent>,
-) {
-    for create_scene in create_scene_command_reader.iter() {
-        let (new_scene_path, new_scene_parent) = match &create_scene.scene {
-            Some(scene_descriptor) => {
-                let path = scene_descriptor
-                    .get_path()
-                    .as_path()
-                    .to_str()
-                    .unwrap()
-                    .to_string();
-                let parent = scene_descriptor
-                    .get_path()
-                    .as_path()
-                    .parent()
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .to_string();
-                (path, parent)
-            }
-            None => continue,
-        };
-        if create_and_save_scene(new_scene_path, new_scene_parent) {
-            execute_command_writer.send(
-                ExecuteCommandEvent { inner: create_scene.recreate() }
-            );
+    let serialized_scene = scene
+        .serialize_ron(&type_registry)
+        .map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serialized_scene)?;
+    Ok(())
+}
+
+impl ApplyCommand for CreateSceneCommand {
+    fn apply(&self, _world: &mut World) -> Result<(), CommandError> {
+        match &self.scene {
+            Some(scene_descriptor) => create_and_save_scene(&scene_descriptor.get_path()),
+            None => Err("No scene to create".to_string().into()),
         }
     }
 
-    for undo_redo_event in undo_redo_command_reader.iter() {
-        if undo_redo_event.cmd_type() != TypeId::of::<CreateSceneCommand>() {
-            continue;
+    fn revert(&self, _world: &mut World) -> Result<(), CommandError> {
+        if let Some(scene_descriptor) = &self.scene {
+            std::fs::remove_file(scene_descriptor.get_path())?;
         }
+        Ok(())
+    }
+}
+
+/// Marker of the model instances spawned by InstantiateModelCommand
+#[derive(Component, Debug)]
+pub struct ModelInstance {
+    pub id: Uuid,
+}
+
+/// Spawn scene of glTF model into the open scene. Spawned entity is tracked by the instance id,
+/// so undo and redo always refer to the same instance
+#[derive(Debug, Clone)]
+pub struct InstantiateModelCommand {
+    /// Path of the model file
+    pub model: PathBuf,
+    /// Label of the model scene, e.g. "Scene0"
+    pub scene_label: String,
+    pub instance: Uuid,
+}
+
+impl InstantiateModelCommand {
+    pub fn new(model: PathBuf, scene_label: String) -> Self {
+        Self {
+            model,
+            scene_label,
+            instance: Uuid::new_v4(),
+        }
+    }
+}
+
+impl Command for InstantiateModelCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
 
-        let create_scene_event: &CreateSceneCommand = undo_redo_event.inner.as_any().downcast_ref().unwrap();
-        match undo_redo_event.mode {
-            CommandExecuteDirection::Undo => {
-                if let Some(scene_descriptor) = &create_scene_event.scene {
-                    match std::fs::remove_file(scene_descriptor.get_path()) {
-                        Ok(_) => (),
-                        Err(e) => match e.kind() {
-                            std::io::ErrorKind::PermissionDenied => {
-                                error!("Failed to delete file: {:?}", scene_descriptor.get_path());
-                            },
-                            _ => (),
-                        },
-                    };
-                }
-            },
-            CommandExecuteDirection::Redo => {
-                let recreate_scene: &CreateSceneCommand = undo_redo_event.inner.as_any().downcast_ref().unwrap();
-                let path = recreate_scene.scene.clone().unwrap().get_path();
-                create_and_save_scene(path.to_str().unwrap().to_string(), path.parent().unwrap().to_str().unwrap().to_string());
-            },
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<InstantiateModelCommand>()
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.model.as_os_str().len() + self.scene_label.len()
+    }
+
+    fn describe(&self) -> String {
+        let model = self.model.file_name().unwrap_or(self.model.as_os_str());
+        format!(
+            "Instantiate {}#{}",
+            model.to_string_lossy(),
+            self.scene_label
+        )
+    }
+}
+
+impl ApplyCommand for InstantiateModelCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        let scene_path = AssetPath::new(self.model.clone(), Some(self.scene_label.clone()));
+        let scene = world.resource::<AssetServer>().load(scene_path);
+        let name = self.model.file_stem().unwrap_or(self.model.as_os_str());
+        let guid = world
+            .get_resource::<AssetGuidIndex>()
+            .and_then(|guids| guids.guid(&self.model));
+        let mut instance = world.spawn();
+        instance
+            .insert_bundle(SceneBundle {
+                scene,
+                ..Default::default()
+            })
+            .insert(Name::new(name.to_string_lossy().to_string()))
+            .insert(ModelInstance { id: self.instance });
+        // Saved scenes refer to the model by GUID, so the model can be moved afterwards
+        if let Some(guid) = guid {
+            instance.insert(AssetReference::new(guid));
         }
+        Ok(())
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        let entity = world
+            .query::<(Entity, &ModelInstance)>()
+            .iter(world)
+            .find(|(_, instance)| instance.id == self.instance)
+            .map(|(entity, _)| entity)
+            .ok_or_else(|| format!("Instance of {:?} no longer exists", self.model))?;
+        despawn_with_children_recursive(world, entity);
+        Ok(())
     }
 }
 
@@ -186,3 +266,99 @@ pub fn open_scene_system(mut select_scene_reader: EventReader<OpenSceneCommand>)
         println!("Selected scene bitch");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::testing::CommandTestApp;
+    use bevy::asset::AssetPlugin;
+
+    fn create_scene_command(app: &CommandTestApp, name: &str) -> CreateSceneCommand {
+        CreateSceneCommand {
+            scene: Some(SceneAssetDescriptor {
+                name: name.into(),
+                path: app.path("scenes").join(name),
+            }),
+        }
+    }
+
+    #[test]
+    fn scene_file_name_has_scene_extension() {
+        assert_eq!(scene_file_name("level"), "level.scn.ron");
+        assert_eq!(scene_file_name("level.ron"), "level.scn.ron");
+        assert_eq!(scene_file_name(" level.scn.ron "), "level.scn.ron");
+    }
+
+    #[test]
+    fn scene_file_follows_undo_and_redo() {
+        let mut app = CommandTestApp::new();
+        app.app.add_editor_command::<CreateSceneCommand>();
+        let command = create_scene_command(&app, "level.scn.ron");
+        let scene_path = app.path("scenes/level.scn.ron");
+
+        app.send(command);
+        assert!(scene_path.exists());
+        assert_eq!(app.queue().pointer, Some(0));
+
+        app.undo();
+        assert!(!scene_path.exists());
+        app.redo();
+        assert!(scene_path.exists());
+    }
+
+    #[test]
+    fn existing_scene_is_not_overwritten() {
+        let mut app = CommandTestApp::new();
+        app.app.add_editor_command::<CreateSceneCommand>();
+        std::fs::create_dir_all(app.path("scenes")).unwrap();
+        std::fs::write(app.path("scenes/level.scn.ron"), "keep me").unwrap();
+
+        app.send(create_scene_command(&app, "level.scn.ron"));
+        assert!(app.queue().items.is_empty());
+        assert_eq!(app.drain_failures().len(), 1);
+        let content = std::fs::read_to_string(app.path("scenes/level.scn.ron")).unwrap();
+        assert_eq!(content, "keep me");
+    }
+
+    #[test]
+    fn model_instance_follows_undo_and_redo() {
+        let mut app = CommandTestApp::new();
+        app.app
+            .add_plugin(AssetPlugin)
+            .add_editor_command::<InstantiateModelCommand>();
+        let instances = |app: &mut CommandTestApp| {
+            let mut query = app.app.world.query::<&ModelInstance>();
+            query.iter(&app.app.world).count()
+        };
+
+        let model = app.path("models/tree.glb");
+        let guid = Uuid::new_v4();
+        let mut guids = AssetGuidIndex::default();
+        guids.insert(guid, model.clone());
+        app.app.insert_resource(guids);
+        app.send(InstantiateModelCommand::new(model, "Scene0".into()));
+        assert_eq!(instances(&mut app), 1);
+        let mut references = app.app.world.query::<&AssetReference>();
+        assert_eq!(
+            references.single(&app.app.world),
+            &AssetReference::new(guid)
+        );
+        app.undo();
+        assert_eq!(instances(&mut app), 0);
+        app.redo();
+        assert_eq!(instances(&mut app), 1);
+    }
+
+    #[test]
+    fn failed_redo_moves_pointer_back() {
+        let mut app = CommandTestApp::new();
+        app.app.add_editor_command::<CreateSceneCommand>();
+        app.send(create_scene_command(&app, "level.scn.ron"));
+        app.undo();
+        std::fs::write(app.path("scenes/level.scn.ron"), "created outside").unwrap();
+
+        app.redo();
+        assert_eq!(app.queue().pointer, None);
+        assert_eq!(app.drain_failures().len(), 1);
+    }
+}
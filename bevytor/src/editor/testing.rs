@@ -0,0 +1,213 @@
+//! Headless harness for testing editor commands without window, egui or keyboard input
+
+use crate::editor::assets::asset_loader::AssetDirectory;
+use crate::editor::commands::dispatch::{AddEditorCommand, ApplyCommand, SendCommandEvent};
+use crate::editor::commands::persistence::{CommandHistoryFile, RegisterEditorCommand};
+use crate::editor::commands::{
+    Command, CommandAny, CommandError, CommandFailedEvent, CommandQueue, EditorCommandsPlugin,
+    UndoRedoCommandEvent,
+};
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use crate::editor::ui::asset_browser::{EnterDirectoryCommand, SelectedDirectory};
+use bevy::ecs::event::Events;
+use bevy::ecs::system::{Resource, SystemState};
+use bevy::prelude::*;
+use bevy::time::{create_time_channels, TimeSender};
+use bevy::utils::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Minimal App with editor command systems and a temporary directory for file side effects.
+/// Time advances only through `advance_time`
+pub struct CommandTestApp {
+    pub app: App,
+    pub temp_dir: TempDir,
+    time_sender: TimeSender,
+    now: Instant,
+}
+
+impl CommandTestApp {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(EditorCommandsPlugin)
+            .add_editor_command::<RecordingCommand>()
+            .register_editor_command::<RecordingCommand>("bevytor::RecordingCommand")
+            .init_resource::<CommandLog>();
+        // Tests must never touch the history of the real project
+        app.world.remove_resource::<CommandHistoryFile>();
+        let (time_sender, time_receiver) = create_time_channels();
+        app.insert_resource(time_receiver);
+
+        Self {
+            app,
+            temp_dir: tempfile::tempdir().unwrap(),
+            time_sender,
+            now: Instant::now(),
+        }
+    }
+
+    /// Create given directories inside the temporary directory and use it as the asset root.
+    /// Registers EnterDirectoryCommand and selects the root directory
+    pub fn with_asset_directories(mut self, directories: &[&str]) -> Self {
+        let mut root = AssetDirectory::new(self.temp_dir.path().to_path_buf());
+        for directory in directories {
+            let path = self.path(directory);
+            std::fs::create_dir_all(&path).unwrap();
+            root.children_directories.push(AssetDirectory::new(path));
+        }
+
+        let selected = SelectedDirectory::from(&root);
+        self.app
+            .insert_resource(root)
+            .insert_resource(selected)
+            .add_editor_command::<EnterDirectoryCommand>();
+        self
+    }
+
+    /// Absolute path of an entry in the temporary directory
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.temp_dir.path().join(relative)
+    }
+
+    /// Send command to its handler and let it reach the CommandQueue
+    pub fn send<T: ApplyCommand>(&mut self, command: T) {
+        self.app.world.send_event(SendCommandEvent::new(command));
+        self.update();
+    }
+
+    pub fn undo(&mut self) {
+        self.app.world.send_event(EditorActionEvent {
+            action: EditorAction::Undo,
+        });
+        self.update();
+    }
+
+    pub fn redo(&mut self) {
+        self.app.world.send_event(EditorActionEvent {
+            action: EditorAction::Redo,
+        });
+        self.update();
+    }
+
+    /// Undo or redo everything required to reach `target`, as the history panel does
+    pub fn move_to(&mut self, target: Option<usize>) {
+        let mut state: SystemState<(ResMut<CommandQueue>, EventWriter<UndoRedoCommandEvent>)> =
+            SystemState::new(&mut self.app.world);
+        let (mut queue, mut commands_writer) = state.get_mut(&mut self.app.world);
+        queue.move_to(target, &mut commands_writer);
+        self.update();
+    }
+
+    /// Run a single frame. Commands sent before it are applied and stored in the CommandQueue
+    /// within that frame
+    pub fn update(&mut self) {
+        self.time_sender.0.send(self.now).unwrap();
+        self.app.update();
+    }
+
+    /// Move the time seen by the following frames forward
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+
+    pub fn queue(&self) -> &CommandQueue {
+        self.app.world.resource::<CommandQueue>()
+    }
+
+    pub fn queue_mut(&mut self) -> Mut<'_, CommandQueue> {
+        self.app.world.resource_mut::<CommandQueue>()
+    }
+
+    pub fn resource<T: Resource>(&self) -> &T {
+        self.app.world.resource::<T>()
+    }
+
+    /// Recorded log of applied and reverted RecordingCommands
+    pub fn log(&self) -> &[String] {
+        &self.resource::<CommandLog>().entries
+    }
+
+    /// Description of all the failures reported since the last call
+    pub fn drain_failures(&mut self) -> Vec<String> {
+        self.app
+            .world
+            .resource_mut::<Events<CommandFailedEvent>>()
+            .drain()
+            .map(|failure| failure.to_string())
+            .collect()
+    }
+}
+
+/// Log filled by RecordingCommand handler
+#[derive(Default)]
+pub struct CommandLog {
+    pub entries: Vec<String>,
+}
+
+/// Command writing "apply <name>" and "revert <name>" entries to CommandLog.
+/// Can be configured to fail, or to merge with other RecordingCommands
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RecordingCommand {
+    pub name: String,
+    pub fail: bool,
+    pub mergeable: bool,
+}
+
+impl RecordingCommand {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Command for RecordingCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<RecordingCommand>()
+    }
+
+    fn merge(&mut self, next: &dyn CommandAny) -> bool {
+        match next.as_any().downcast_ref::<RecordingCommand>() {
+            Some(next) if self.mergeable && next.mergeable => {
+                self.name = format!("{}+{}", self.name, next.name);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl ApplyCommand for RecordingCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        self.record(world, "apply")
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        self.record(world, "revert")
+    }
+}
+
+impl RecordingCommand {
+    fn record(&self, world: &mut World, action: &str) -> Result<(), CommandError> {
+        if self.fail {
+            return Err(format!("{} {} failed", action, self.name).into());
+        }
+        world
+            .resource_mut::<CommandLog>()
+            .entries
+            .push(format!("{} {}", action, self.name));
+        Ok(())
+    }
+}
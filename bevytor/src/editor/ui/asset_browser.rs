@@ -1,13 +1,46 @@
 use crate::editor::assets::asset_loader::{AssetDirectory, AssetType, EditorAssets};
-use crate::editor::commands::{
-    Command, CommandAny, CommandExecuteDirection, ExecuteCommandEvent, UndoRedoCommandEvent,
+use crate::editor::assets::asset_types::AssetTypeRegistry;
+use crate::editor::assets::dependencies::AssetDependencyGraph;
+use crate::editor::assets::file_ops::{
+    renamed_path, sweep_asset_trash_system, AssetTrash, CreateDirectoryCommand, DeleteAssetCommand,
+    DuplicateAssetCommand, FileOperation, FileOperationWriters, MoveAssetCommand,
 };
-use crate::editor::run_if_post_initializing_assets;
+use crate::editor::assets::model::{model_sub_assets, ModelSubAsset, ModelSubAssetKind};
+use crate::editor::assets::watcher::{
+    asset_watcher_system, AssetAddedEvent, AssetChange, AssetMovedEvent, AssetRemovedEvent,
+};
+use crate::editor::commands::dispatch::{
+    AddEditorCommand, ApplyCommand, CommandBatch, CommandWriter,
+};
+use crate::editor::commands::persistence::{load_command_history_system, RegisterEditorCommand};
+use crate::editor::commands::{Command, CommandAny, CommandError};
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use crate::editor::scene::InstantiateModelCommand;
+use crate::editor::ui::asset_navigation::{
+    breadcrumbs, directory_navigation_system, DirectoryHistory, Navigation,
+};
+use crate::editor::ui::asset_search::{AssetSearch, SearchEntry};
+use crate::editor::ui::asset_selection::{
+    AssetSelection, SelectAssetsCommand, SelectedAssets, SelectionModifiers,
+};
+use crate::editor::ui::asset_table::{
+    format_modified, format_size, AssetRow, AssetSort, AssetSortColumn, AssetTableRows,
+    AssetViewMode, DiskDetails,
+};
+use crate::editor::ui::dependencies::{DependencyView, DependencyViewKind};
+use crate::editor::ui::preview::AssetPreview;
 use crate::editor::ui::widgets::{self, draw_directory_hierarchy};
+use crate::editor::{run_if_post_initializing_assets, EditorStateLabel};
 use bevy::app::{App, Plugin};
+use bevy::ecs::change_detection::DetectChanges;
 use bevy::ecs::system::{Res, ResMut};
-use bevy::prelude::{Commands, EventReader, EventWriter, SystemSet};
-use bevy_egui::egui::ScrollArea;
+use bevy::gltf::Gltf;
+use bevy::prelude::{Assets, EventReader, ParallelSystemDescriptorCoercion, SystemSet, World};
+use bevy::utils::HashSet;
+use bevy_egui::egui::{
+    show_tooltip_text, Align2, Button, Color32, Context, Grid, Id, Key, Label, Modifiers, Pos2,
+    Rect, Response, RichText, ScrollArea, Sense, Slider, TextEdit, Window,
+};
 use bevy_egui::{
     egui::{
         panel::{SidePanel, TopBottomPanel},
@@ -15,8 +48,10 @@ use bevy_egui::{
     },
     EguiContext,
 };
+use serde::{Deserialize, Serialize};
 use std::any::TypeId;
-use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone)]
 pub enum Selection {
@@ -32,7 +67,7 @@ pub enum SelectionCommand {
 
 /// Command used for notification about SelectDirectory events.
 /// Designed with support for Undo and Redo in mind
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EnterDirectoryCommand {
     pub previous_selected_directory: PathBuf,
     pub new_selected_directory: PathBuf,
@@ -49,6 +84,70 @@ impl Command for EnterDirectoryCommand {
     fn command_type(&self) -> TypeId {
         TypeId::of::<EnterDirectoryCommand>()
     }
+
+    /// Walking through several directories in a row collapses into a single jump
+    fn merge(&mut self, next: &dyn CommandAny) -> bool {
+        match next.as_any().downcast_ref::<EnterDirectoryCommand>() {
+            Some(next) if next.previous_selected_directory == self.new_selected_directory => {
+                self.new_selected_directory = next.new_selected_directory.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.previous_selected_directory.as_os_str().len()
+            + self.new_selected_directory.as_os_str().len()
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![TypeId::of::<SelectedDirectory>()]
+    }
+
+    fn describe(&self) -> String {
+        let name = self
+            .new_selected_directory
+            .file_name()
+            .unwrap_or_else(|| self.new_selected_directory.as_os_str());
+        format!("Enter directory {}", name.to_string_lossy())
+    }
+}
+
+impl EnterDirectoryCommand {
+    /// Create command entering given directory. Returns None if it is already selected
+    pub fn new(
+        selected_directory: &SelectedDirectory,
+        new_selected_directory: PathBuf,
+    ) -> Option<Self> {
+        (selected_directory.get_path() != new_selected_directory).then(|| Self {
+            previous_selected_directory: selected_directory.get_path(),
+            new_selected_directory,
+        })
+    }
+}
+
+impl ApplyCommand for EnterDirectoryCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        enter_directory(world, &self.new_selected_directory)
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        enter_directory(world, &self.previous_selected_directory)
+    }
+}
+
+/// Replace SelectedDirectory with directory found under given path.
+/// Restored history may point to directories removed in the meantime, hence the error
+fn enter_directory(world: &mut World, path: &Path) -> Result<(), CommandError> {
+    let directory = world
+        .resource::<AssetDirectory>()
+        .find_by_path(path)
+        .map(SelectedDirectory::from)
+        .ok_or_else(|| missing_directory_error(path))?;
+    *world.resource_mut::<SelectedDirectory>() = directory;
+    Ok(())
 }
 
 /// Plugin for displaying and manipulating assets in file system like manner.
@@ -56,19 +155,50 @@ impl Command for EnterDirectoryCommand {
 pub struct AssetBrowserPlugin;
 impl Plugin for AssetBrowserPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<EnterDirectoryCommand>()
+        app.add_editor_command::<EnterDirectoryCommand>()
+            .register_editor_command::<EnterDirectoryCommand>("bevytor::EnterDirectoryCommand")
+            .add_editor_command::<MoveAssetCommand>()
+            .register_editor_command::<MoveAssetCommand>("bevytor::MoveAssetCommand")
+            .add_editor_command::<DeleteAssetCommand>()
+            .register_editor_command::<DeleteAssetCommand>("bevytor::DeleteAssetCommand")
+            .add_editor_command::<DuplicateAssetCommand>()
+            .register_editor_command::<DuplicateAssetCommand>("bevytor::DuplicateAssetCommand")
+            .add_editor_command::<CreateDirectoryCommand>()
+            .register_editor_command::<CreateDirectoryCommand>("bevytor::CreateDirectoryCommand")
+            .add_editor_command::<CommandBatch<MoveAssetCommand>>()
+            .register_editor_command::<CommandBatch<MoveAssetCommand>>("bevytor::MoveAssetBatch")
+            .add_editor_command::<CommandBatch<DeleteAssetCommand>>()
+            .register_editor_command::<CommandBatch<DeleteAssetCommand>>(
+                "bevytor::DeleteAssetBatch",
+            )
+            .add_editor_command::<CommandBatch<DuplicateAssetCommand>>()
+            .register_editor_command::<CommandBatch<DuplicateAssetCommand>>(
+                "bevytor::DuplicateAssetBatch",
+            )
+            .add_editor_command::<SelectAssetsCommand>()
+            .register_editor_command::<SelectAssetsCommand>("bevytor::SelectAssetsCommand")
             .insert_resource(AssetBrowserSettings::default())
             .insert_resource(SelectedDirectory::default())
-            .add_startup_system_set(
-                SystemSet::new()
-                    .with_run_criteria(run_if_post_initializing_assets)
-                    .with_system(selection_setup),
-            )
+            .init_resource::<SelectedAssets>()
+            .init_resource::<AssetBrowserState>()
+            .init_resource::<AssetTrash>()
+            .add_startup_system(sweep_asset_trash_system.after(load_command_history_system))
+            .add_event::<AssetAddedEvent>()
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
             .add_system_set(
                 SystemSet::new()
                     .with_run_criteria(run_if_post_initializing_assets)
-                    .with_system(asset_browser_system)
-                    .with_system(select_directory_system),
+                    .with_system(selection_setup)
+                    .with_system(
+                        refresh_selected_directory_system
+                            .after(selection_setup)
+                            .after(asset_watcher_system),
+                    )
+                    .with_system(
+                        directory_navigation_system.after(refresh_selected_directory_system),
+                    )
+                    .with_system(asset_browser_system.after(directory_navigation_system)),
             );
     }
 }
@@ -132,6 +262,11 @@ impl SelectedDirectory {
     pub fn get_path(&self) -> PathBuf {
         self.details.get_path()
     }
+
+    /// Shallow copy of the directory, children directories are listed without their content
+    pub fn directory(&self) -> &AssetDirectory {
+        &self.details
+    }
 }
 
 /// Resource containing data about AssetBrowser settings
@@ -139,97 +274,689 @@ pub struct AssetBrowserSettings {
     /// Default height of the asset browser
     /// TODO: Change to use screen %
     default_height: f32,
-    /// Number of thumbnails per row.
-    /// AssetBrowser will scale all the thumbnails to satisfy this number
-    thumbnails_per_row: u32,
+    /// Size of the thumbnails including their margin, adjusted by the zoom slider
+    thumbnail_size: f32,
     /// Default directory hierarchy width
     directory_hierarchy_widht: f32,
+    /// Thumbnail grid or table with details
+    view_mode: AssetViewMode,
+    /// Sorting of the table
+    sort: AssetSort,
 }
 
 impl Default for AssetBrowserSettings {
     fn default() -> Self {
         Self {
             default_height: 200.0,
-            thumbnails_per_row: 8,
+            thumbnail_size: 128.0,
             directory_hierarchy_widht: 350.0,
+            view_mode: AssetViewMode::Grid,
+            sort: AssetSort::default(),
+        }
+    }
+}
+
+/// Range of the thumbnail zoom slider
+const THUMBNAIL_SIZE_RANGE: RangeInclusive<f32> = 48.0..=256.0;
+
+/// Resource containing state of the asset browser which is not worth storing in the history
+#[derive(Default)]
+pub struct AssetBrowserState {
+    /// Models whose sub-assets are displayed next to them
+    pub expanded_models: HashSet<PathBuf>,
+    /// Asset or directory being renamed, together with the edited name
+    pub renaming: Option<(PathBuf, String)>,
+    /// Assets and directories being dragged onto another directory
+    pub dragged: Vec<PathBuf>,
+    /// Operations breaking references, waiting for confirmation. Contains the warning
+    pending: Option<(Vec<FileOperation>, String)>,
+    /// Start of the rubber band selection, in screen coordinates
+    rubber_band: Option<Pos2>,
+    /// Search replacing content of the selected directory with matches from the whole hierarchy
+    pub search: AssetSearch,
+    /// Sizes and modification times displayed in the table
+    disk_details: DiskDetails,
+    /// Sorted rows of the table
+    table_rows: AssetTableRows,
+    /// Directories visited with the back and forward buttons
+    pub navigation: DirectoryHistory,
+}
+
+impl AssetBrowserState {
+    pub fn toggle_model(&mut self, model: PathBuf) {
+        if !self.expanded_models.remove(&model) {
+            self.expanded_models.insert(model);
         }
     }
 }
 
-/// Setup system, right now only inserts SelectedDirectory resource. Should be moved to build function
+/// Setup system, selects the root directory once assets are scanned
 pub fn selection_setup(
-    mut _commands: Commands,
+    editor_state: Res<EditorStateLabel>,
     root_directory: Res<AssetDirectory>,
     mut currently_selected_directory: ResMut<SelectedDirectory>,
-    mut select_directory_event_writer: EventWriter<EnterDirectoryCommand>,
 ) {
+    if !editor_state.is_changed() {
+        return;
+    }
     *currently_selected_directory = SelectedDirectory::from(root_directory.as_ref());
-    let select_command = EnterDirectoryCommand {
-        new_selected_directory: root_directory.get_path(),
-        previous_selected_directory: currently_selected_directory.get_path(),
+}
+
+/// Re-derive SelectedDirectory when its content changed on disk. Follows the directory when
+/// it was moved and falls back to the closest existing ancestor when it was removed
+pub fn refresh_selected_directory_system(
+    root_directory: Res<AssetDirectory>,
+    mut selected_directory: ResMut<SelectedDirectory>,
+    mut added_reader: EventReader<AssetAddedEvent>,
+    mut removed_reader: EventReader<AssetRemovedEvent>,
+    mut moved_reader: EventReader<AssetMovedEvent>,
+) {
+    let changes: Vec<AssetChange> = added_reader
+        .iter()
+        .map(|event| AssetChange::Added(event.path.clone()))
+        .chain(
+            removed_reader
+                .iter()
+                .map(|event| AssetChange::Removed(event.path.clone())),
+        )
+        .chain(moved_reader.iter().map(|event| AssetChange::Moved {
+            from: event.from.clone(),
+            to: event.to.clone(),
+        }))
+        .collect();
+    sync_selected_directory(&root_directory, &mut selected_directory, &changes);
+}
+
+/// Re-derive SelectedDirectory if any of the changes touched it
+pub fn sync_selected_directory(
+    root_directory: &AssetDirectory,
+    selected_directory: &mut SelectedDirectory,
+    changes: &[AssetChange],
+) {
+    let mut selected_path = selected_directory.get_path();
+    let mut changed = false;
+    let mut touches = |path: &Path, selected_path: &Path| {
+        changed |= path.parent() == Some(selected_path) || selected_path.starts_with(path);
     };
-    select_directory_event_writer.send(select_command);
+    for change in changes {
+        match change {
+            AssetChange::Added(path) | AssetChange::Removed(path) => touches(path, &selected_path),
+            AssetChange::Moved { from, to } => {
+                touches(from, &selected_path);
+                touches(to, &selected_path);
+                if let Ok(relative) = selected_path.strip_prefix(from) {
+                    selected_path = to.join(relative);
+                }
+            }
+        }
+    }
+    if !changed {
+        return;
+    }
+
+    let directory = selected_path
+        .ancestors()
+        .find_map(|path| root_directory.find_by_path(path))
+        .unwrap_or(root_directory);
+    *selected_directory = SelectedDirectory::from(directory);
+}
+
+/// Action requested by interacting with the thumbnails
+enum AssetAction {
+    EnterDirectory(PathBuf),
+    /// Enter directory containing the search result and select it
+    ShowInFolder(PathBuf),
+    /// Show details of the asset in the preview window
+    Preview(AssetType),
+    /// Thumbnail was clicked, changes the selection. Plain click on the asset also previews it
+    Click {
+        path: PathBuf,
+        modifiers: SelectionModifiers,
+    },
+    Select(SelectedAssets),
+    InstantiateModel {
+        model: PathBuf,
+        scene_label: String,
+    },
+    /// List scenes referencing the asset or directory
+    FindReferences(PathBuf),
+    /// List assets referenced by the scene
+    ShowDependencies(PathBuf),
+    /// Open rename dialog of the asset or directory
+    Rename(PathBuf),
+    /// Start dragging the asset or directory, together with the rest of the selection
+    Drag(PathBuf),
+    File(Vec<FileOperation>),
+}
+
+fn selection_modifiers(modifiers: &Modifiers) -> SelectionModifiers {
+    SelectionModifiers {
+        toggle: modifiers.command,
+        extend: modifiers.shift,
+    }
+}
+
+/// Operation on one of the selected entries applies to the whole selection
+fn expand_to_selection(operation: FileOperation, selected: &[PathBuf]) -> Vec<FileOperation> {
+    match operation {
+        FileOperation::Delete(path) if selected.contains(&path) => selected
+            .iter()
+            .cloned()
+            .map(FileOperation::Delete)
+            .collect(),
+        FileOperation::Duplicate(path) if selected.contains(&path) => selected
+            .iter()
+            .cloned()
+            .map(FileOperation::Duplicate)
+            .collect(),
+        operation => vec![operation],
+    }
+}
+
+/// Context menu entries shared by assets and directories
+fn file_menu(ui: &mut Ui, path: &Path, action: &mut Option<AssetAction>) {
+    if ui.button("Rename").clicked() {
+        *action = Some(AssetAction::Rename(path.to_path_buf()));
+        ui.close_menu();
+    }
+    if ui.button("Duplicate").clicked() {
+        *action = Some(AssetAction::File(vec![FileOperation::Duplicate(
+            path.to_path_buf(),
+        )]));
+        ui.close_menu();
+    }
+    if ui.button("Delete").clicked() {
+        *action = Some(AssetAction::File(vec![FileOperation::Delete(
+            path.to_path_buf(),
+        )]));
+        ui.close_menu();
+    }
+}
+
+/// Context menu of directories, both in the tree view and among the thumbnails.
+/// Root directory can't be renamed, duplicated or deleted
+fn directory_menu(ui: &mut Ui, path: &Path, is_root: bool, action: &mut Option<AssetAction>) {
+    if ui.button("New folder").clicked() {
+        *action = Some(AssetAction::File(vec![FileOperation::NewFolder(
+            path.to_path_buf(),
+        )]));
+        ui.close_menu();
+    }
+    if !is_root {
+        file_menu(ui, path, action);
+    }
+    if ui.button("Find references").clicked() {
+        *action = Some(AssetAction::FindReferences(path.to_path_buf()));
+        ui.close_menu();
+    }
+}
+
+/// Directory accepts dragged entry unless it is the entry itself, its content or its parent
+fn accepts_drop(directory: &Path, dragged: &Path) -> bool {
+    !directory.starts_with(dragged) && dragged.parent() != Some(directory)
+}
+
+/// Moves of the dragged entries into the directory, entries it doesn't accept are skipped
+fn drop_operations(directory: &Path, dragged: &[PathBuf]) -> Vec<FileOperation> {
+    dragged
+        .iter()
+        .filter(|dragged| accepts_drop(directory, dragged))
+        .filter_map(|dragged| {
+            Some(FileOperation::Move {
+                from: dragged.clone(),
+                to: directory.join(dragged.file_name()?),
+            })
+        })
+        .collect()
+}
+
+/// Highlight the directory and store it as the drop target when dragged entry is above it
+fn update_drop_target(
+    ui: &Ui,
+    response: &Response,
+    directory: &Path,
+    state: &AssetBrowserState,
+    drop_target: &mut Option<PathBuf>,
+) {
+    let into_itself = state
+        .dragged
+        .iter()
+        .any(|dragged| directory.starts_with(dragged));
+    let accepted = state
+        .dragged
+        .iter()
+        .any(|dragged| accepts_drop(directory, dragged));
+    if !into_itself && accepted && ui.rect_contains_pointer(response.rect) {
+        let stroke = ui.visuals().selection.stroke;
+        ui.painter().rect_stroke(response.rect, 2.0, stroke);
+        *drop_target = Some(directory.to_path_buf());
+    }
 }
 
 /// As name suggests....
 /// Draws all the directories and assets contained within currently
 /// selected directory (Res<SelectedDirectory>)
 const DEFAULT_EGUI_MARGIN: Vec2 = Vec2::new(16.0, 16.0);
+#[allow(clippy::too_many_arguments)]
 fn draw_assets(
     ui: &mut Ui,
-    images_per_row: u32,
+    thumbnail_size: f32,
     asset_directory: &AssetDirectory,
     editor_assets: &EditorAssets,
-) -> Option<PathBuf> {
-    let mut selected_directory_path: Option<PathBuf> = None;
+    asset_types: &AssetTypeRegistry,
+    models: &Assets<Gltf>,
+    state: &AssetBrowserState,
+    selected: &SelectedAssets,
+    drop_target: &mut Option<PathBuf>,
+    grid: &mut Vec<(PathBuf, Rect)>,
+) -> Option<AssetAction> {
+    let mut action: Option<AssetAction> = None;
+    let modifiers = selection_modifiers(&ui.input().modifiers);
     ui.with_layout(
         Layout::left_to_right()
             .with_cross_align(Align::Min)
             .with_main_wrap(true),
         |ui| {
-            let available_space = ui.available_size_before_wrap();
-            let thumbnail_size = available_space.x / images_per_row as f32;
             for d in asset_directory.children_directories.iter() {
-                if widgets::thumbnail(
-                    ui,
-                    d.name.to_string_lossy().to_string(),
-                    Vec2::splat(thumbnail_size) - DEFAULT_EGUI_MARGIN,
-                    editor_assets.directory_icon,
-                )
-                .double_clicked()
-                {
-                    selected_directory_path = Some(d.path.to_path_buf());
+                let thumbnail = widgets::Thumbnail {
+                    label: d.name.to_string_lossy().to_string(),
+                    size: Vec2::splat(thumbnail_size) - DEFAULT_EGUI_MARGIN,
+                    texture_id: editor_assets.directory_icon,
+                    selected: selected.contains(&d.path),
+                };
+                let response = ui.add(thumbnail);
+                grid.push((d.path.to_path_buf(), response.rect));
+                if response.clicked() {
+                    action = Some(AssetAction::Click {
+                        path: d.path.to_path_buf(),
+                        modifiers,
+                    });
+                }
+                if response.double_clicked() {
+                    action = Some(AssetAction::EnterDirectory(d.path.to_path_buf()));
                 }
+                if response.drag_started() {
+                    action = Some(AssetAction::Drag(d.path.to_path_buf()));
+                }
+                update_drop_target(ui, &response, &d.path, state, drop_target);
+                response.context_menu(|ui| directory_menu(ui, &d.path, false, &mut action));
             }
 
             for asset in asset_directory.assets.iter() {
-                let texture_id = match asset {
-                    AssetType::Image(image) => image.egui_texture_id,
-                    AssetType::Scene(_) => editor_assets.map_icon,
-                };
+                let texture_id = asset_types
+                    .texture_id(asset)
+                    .unwrap_or(editor_assets.file_icon);
                 let thumbnail = widgets::Thumbnail {
                     label: asset.get_name(),
                     size: Vec2::splat(thumbnail_size) - DEFAULT_EGUI_MARGIN,
                     texture_id,
-                    selected: false,
-                    ..Default::default()
+                    selected: selected.contains(&asset.get_path()),
+                };
+                let response = ui.add(thumbnail);
+                grid.push((asset.get_path(), response.rect));
+                if response.clicked() {
+                    action = Some(AssetAction::Click {
+                        path: asset.get_path(),
+                        modifiers,
+                    });
+                }
+                if response.drag_started() {
+                    action = Some(AssetAction::Drag(asset.get_path()));
+                }
+
+                let sub_assets = match asset {
+                    AssetType::Model(model) => models
+                        .get(&model.bevy_handle)
+                        .map(model_sub_assets)
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                let instantiate = |sub_asset: &ModelSubAsset| AssetAction::InstantiateModel {
+                    model: asset.get_path(),
+                    scene_label: sub_asset.label.clone(),
+                };
+                response.context_menu(|ui| {
+                    if ui.button("Find references").clicked() {
+                        action = Some(AssetAction::FindReferences(asset.get_path()));
+                        ui.close_menu();
+                    }
+                    if let AssetType::Scene(scene) = asset {
+                        if ui.button("Show dependencies").clicked() {
+                            action = Some(AssetAction::ShowDependencies(scene.path.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                    if let AssetType::Model(_) = asset {
+                        if ui.button("Import settings").clicked() {
+                            action = Some(AssetAction::Preview(asset.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                    let scenes = sub_assets
+                        .iter()
+                        .filter(|sub_asset| sub_asset.kind == ModelSubAssetKind::Scene);
+                    for scene in scenes {
+                        if ui.button(format!("Instantiate {}", scene.name)).clicked() {
+                            action = Some(instantiate(scene));
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    file_menu(ui, &asset.get_path(), &mut action);
+                });
+
+                if let AssetType::Model(model) = asset {
+                    if !state.expanded_models.contains(&model.path) {
+                        continue;
+                    }
+                    for sub_asset in sub_assets.iter() {
+                        let response = widgets::thumbnail(
+                            ui,
+                            format!("{} {}", sub_asset.kind, sub_asset.name),
+                            Vec2::splat(thumbnail_size) - DEFAULT_EGUI_MARGIN,
+                            texture_id,
+                        );
+                        if sub_asset.kind == ModelSubAssetKind::Scene && response.double_clicked() {
+                            action = Some(instantiate(sub_asset));
+                        }
+                    }
+                }
+            }
+        },
+    );
+
+    action
+}
+
+/// Draws the search box together with a chip for every indexed asset type
+fn draw_search_bar(ui: &mut Ui, search: &mut AssetSearch, root_directory: &AssetDirectory) {
+    let search_box = TextEdit::singleline(&mut search.query)
+        .hint_text("Search, e.g. grass or *.png")
+        .desired_width(200.0);
+    ui.add(search_box);
+    for type_name in search.type_names(root_directory) {
+        let enabled = search.types.contains(type_name);
+        if ui.selectable_label(enabled, type_name).clicked() {
+            search.toggle_type(type_name);
+        }
+    }
+    if search.is_active() && ui.button("Clear").clicked() {
+        search.clear();
+    }
+}
+
+/// Draws search results as a flat grid, each result labeled with its containing folder
+#[allow(clippy::too_many_arguments)]
+fn draw_search_results(
+    ui: &mut Ui,
+    thumbnail_size: f32,
+    results: &[&SearchEntry],
+    editor_assets: &EditorAssets,
+    asset_types: &AssetTypeRegistry,
+    state: &AssetBrowserState,
+    selected: &SelectedAssets,
+    drop_target: &mut Option<PathBuf>,
+    grid: &mut Vec<(PathBuf, Rect)>,
+) -> Option<AssetAction> {
+    let mut action: Option<AssetAction> = None;
+    let modifiers = selection_modifiers(&ui.input().modifiers);
+    if results.is_empty() {
+        ui.label("No matching assets");
+        return None;
+    }
+    ui.with_layout(
+        Layout::left_to_right()
+            .with_cross_align(Align::Min)
+            .with_main_wrap(true),
+        |ui| {
+            for entry in results.iter() {
+                let texture_id = match &entry.asset {
+                    Some(asset) => asset_types
+                        .texture_id(asset)
+                        .unwrap_or(editor_assets.file_icon),
+                    None => editor_assets.directory_icon,
+                };
+                let thumbnail = widgets::Thumbnail {
+                    label: entry
+                        .path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                    size: Vec2::splat(thumbnail_size) - DEFAULT_EGUI_MARGIN,
+                    texture_id,
+                    selected: selected.contains(&entry.path),
                 };
-                ui.add(thumbnail);
+                let response = ui
+                    .vertical(|ui| {
+                        ui.set_max_width(thumbnail_size - DEFAULT_EGUI_MARGIN.x);
+                        let response = ui.add(thumbnail);
+                        ui.add(Label::new(RichText::new(&entry.folder).small().weak()));
+                        response
+                    })
+                    .inner;
+                grid.push((entry.path.clone(), response.rect));
+                if response.clicked() {
+                    action = Some(AssetAction::Click {
+                        path: entry.path.clone(),
+                        modifiers,
+                    });
+                }
+                if response.double_clicked() {
+                    action = Some(match entry.asset {
+                        Some(_) => AssetAction::ShowInFolder(entry.path.clone()),
+                        None => AssetAction::EnterDirectory(entry.path.clone()),
+                    });
+                }
+                if response.drag_started() {
+                    action = Some(AssetAction::Drag(entry.path.clone()));
+                }
+                if entry.asset.is_none() {
+                    update_drop_target(ui, &response, &entry.path, state, drop_target);
+                    response.context_menu(|ui| directory_menu(ui, &entry.path, false, &mut action));
+                    continue;
+                }
+                response.context_menu(|ui| entry_menu(ui, &entry.path, true, &mut action));
             }
         },
     );
 
-    selected_directory_path
+    action
+}
+
+/// Context menu of the asset listed outside of the thumbnail grid of its directory
+fn entry_menu(ui: &mut Ui, path: &Path, show_in_folder: bool, action: &mut Option<AssetAction>) {
+    if show_in_folder && ui.button("Show in folder").clicked() {
+        *action = Some(AssetAction::ShowInFolder(path.to_path_buf()));
+        ui.close_menu();
+    }
+    if ui.button("Find references").clicked() {
+        *action = Some(AssetAction::FindReferences(path.to_path_buf()));
+        ui.close_menu();
+    }
+    ui.separator();
+    file_menu(ui, path, action);
+}
+
+/// Rows of the table, taken from the search results when searching
+fn table_rows(
+    directory: &AssetDirectory,
+    results: Option<Vec<&SearchEntry>>,
+    details: &mut DiskDetails,
+) -> Vec<AssetRow> {
+    let mut rows = Vec::new();
+    match results {
+        Some(results) => {
+            for entry in results {
+                rows.push(match &entry.asset {
+                    Some(asset) => AssetRow::asset(asset, details),
+                    None => AssetRow::directory(&entry.path, details),
+                });
+            }
+        }
+        None => {
+            for child in directory.children_directories.iter() {
+                rows.push(AssetRow::directory(&child.path, details));
+            }
+            for asset in directory.assets.iter() {
+                rows.push(AssetRow::asset(asset, details));
+            }
+        }
+    }
+    rows
+}
+
+/// Draws entries as a table with details, sorted by clicking the column headers
+#[allow(clippy::too_many_arguments)]
+fn draw_asset_table(
+    ui: &mut Ui,
+    rows: &[AssetRow],
+    root_directory: &AssetDirectory,
+    searching: bool,
+    sort: &mut AssetSort,
+    state: &AssetBrowserState,
+    selected: &SelectedAssets,
+    drop_target: &mut Option<PathBuf>,
+    grid: &mut Vec<(PathBuf, Rect)>,
+) -> Option<AssetAction> {
+    let mut action: Option<AssetAction> = None;
+    let modifiers = selection_modifiers(&ui.input().modifiers);
+    // Paths are displayed starting with the root directory name
+    let base = root_directory.path.parent().unwrap_or(&root_directory.path);
+    let table = Grid::new("AssetBrowserTable")
+        .striped(true)
+        .num_columns(AssetSortColumn::ALL.len());
+    table.show(ui, |ui| {
+        for column in AssetSortColumn::ALL {
+            let label = match (sort.column == column, sort.ascending) {
+                (true, true) => format!("{} ⏶", column.label()),
+                (true, false) => format!("{} ⏷", column.label()),
+                (false, _) => column.label().to_string(),
+            };
+            if ui.button(RichText::new(label).strong()).clicked() {
+                sort.click(column);
+            }
+        }
+        ui.end_row();
+
+        for row in rows {
+            let response = ui
+                .selectable_label(selected.contains(&row.path), &row.name)
+                .interact(Sense::click_and_drag());
+            ui.label(row.type_name);
+            ui.label(row.size.map(format_size).unwrap_or_default());
+            ui.label(row.modified.map(format_modified).unwrap_or_default());
+            ui.label(
+                row.path
+                    .strip_prefix(base)
+                    .unwrap_or(&row.path)
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            ui.end_row();
+
+            grid.push((row.path.clone(), response.rect));
+            if response.clicked() {
+                action = Some(AssetAction::Click {
+                    path: row.path.clone(),
+                    modifiers,
+                });
+            }
+            if response.double_clicked() && row.is_directory {
+                action = Some(AssetAction::EnterDirectory(row.path.clone()));
+            } else if response.double_clicked() && searching {
+                action = Some(AssetAction::ShowInFolder(row.path.clone()));
+            }
+            if response.drag_started() {
+                action = Some(AssetAction::Drag(row.path.clone()));
+            }
+            match row.is_directory {
+                true => {
+                    update_drop_target(ui, &response, &row.path, state, drop_target);
+                    response.context_menu(|ui| directory_menu(ui, &row.path, false, &mut action));
+                }
+                false => {
+                    response.context_menu(|ui| entry_menu(ui, &row.path, searching, &mut action));
+                }
+            }
+        }
+    });
+
+    action
+}
+
+/// Draws back, forward and up buttons followed by breadcrumbs of the selected directory
+fn draw_navigation_bar(
+    ui: &mut Ui,
+    root_directory: &AssetDirectory,
+    selected_directory: &Path,
+    history: &mut DirectoryHistory,
+) -> Option<AssetAction> {
+    let mut navigation = None;
+    let buttons = [
+        (Navigation::Back, "⏴", "Back (Alt+Left)"),
+        (Navigation::Forward, "⏵", "Forward (Alt+Right)"),
+        (Navigation::Up, "⏶", "Up (Alt+Up)"),
+    ];
+    for (button_navigation, label, hint) in buttons {
+        let enabled = history.can_navigate(button_navigation, root_directory);
+        let response = ui.add_enabled(enabled, Button::new(label));
+        if response.on_hover_text(hint).clicked() {
+            navigation = Some(button_navigation);
+        }
+    }
+    ui.separator();
+
+    let mut action = navigation
+        .and_then(|navigation| history.navigate(navigation, root_directory))
+        .map(AssetAction::EnterDirectory);
+    let crumbs = breadcrumbs(root_directory, selected_directory);
+    let last = crumbs.len() - 1;
+    for (index, (name, path)) in crumbs.into_iter().enumerate() {
+        if index > 0 {
+            ui.label("›");
+        }
+        let text = match index == last {
+            true => RichText::new(name).strong(),
+            false => RichText::new(name),
+        };
+        if ui.button(text).clicked() {
+            action = Some(AssetAction::EnterDirectory(path));
+        }
+    }
+    action
+}
+
+/// Draws switch between the thumbnail grid and the table, with zoom of the thumbnails
+fn draw_view_controls(ui: &mut Ui, settings: &mut AssetBrowserSettings) {
+    ui.with_layout(Layout::right_to_left(), |ui| {
+        if settings.view_mode == AssetViewMode::Grid {
+            let zoom =
+                Slider::new(&mut settings.thumbnail_size, THUMBNAIL_SIZE_RANGE).show_value(false);
+            ui.add(zoom).on_hover_text("Thumbnail size");
+        }
+        ui.selectable_value(&mut settings.view_mode, AssetViewMode::List, "List");
+        ui.selectable_value(&mut settings.view_mode, AssetViewMode::Grid, "Grid");
+    });
 }
 
 fn draw_side_panel_tree_view(
     ui: &mut Ui,
     root_directory: &AssetDirectory,
     width: f32,
+    state: &AssetBrowserState,
+    action: &mut Option<AssetAction>,
+    drop_target: &mut Option<PathBuf>,
 ) -> Option<Selection> {
     let mut new_selection: Option<Selection> = None;
+    let mut decorate = |ui: &mut Ui, response: Response, directory: &AssetDirectory| {
+        update_drop_target(ui, &response, &directory.path, state, drop_target);
+        let is_root = directory.path == root_directory.path;
+        response.context_menu(|ui| directory_menu(ui, &directory.path, is_root, action));
+    };
     let draw_hierarchy = |ui: &mut Ui| {
-        let potential_selection = draw_directory_hierarchy(ui, &root_directory, false);
+        let potential_selection =
+            draw_directory_hierarchy(ui, root_directory, false, &mut decorate);
         if let Some(selection) = potential_selection {
             new_selection = Some(selection);
         }
@@ -243,15 +970,92 @@ fn draw_side_panel_tree_view(
     new_selection
 }
 
+/// Draws rename dialog of the entry stored in AssetBrowserState::renaming.
+/// Returns the rename once confirmed
+fn draw_rename_dialog(ctx: &Context, state: &mut AssetBrowserState) -> Option<FileOperation> {
+    let (path, name) = state.renaming.as_mut()?;
+    let renamed = renamed_path(path, name);
+    let mut open = true;
+    let mut confirmed = false;
+    let mut cancelled = false;
+    Window::new("Rename")
+        .id(Id::new("AssetBrowserRename"))
+        .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let response = ui.text_edit_singleline(name);
+            let submitted = response.lost_focus() && ui.input().key_pressed(Key::Enter);
+            response.request_focus();
+            if let Err(e) = &renamed {
+                ui.colored_label(Color32::RED, e);
+            }
+            ui.horizontal(|ui| {
+                let rename = ui.add_enabled(renamed.is_ok(), Button::new("Rename"));
+                confirmed = renamed.is_ok() && (submitted || rename.clicked());
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+    if !open || cancelled || confirmed {
+        let (from, _) = state.renaming.take()?;
+        let to = renamed.ok().filter(|to| confirmed && *to != from)?;
+        return Some(FileOperation::Move { from, to });
+    }
+    None
+}
+
+/// Draws warning of the pending operations. Returns the operations once confirmed
+fn draw_confirmation_dialog(
+    ctx: &Context,
+    state: &mut AssetBrowserState,
+) -> Option<Vec<FileOperation>> {
+    let (_, warning) = state.pending.as_ref()?;
+    let mut confirmed = false;
+    let mut cancelled = false;
+    Window::new("Confirm")
+        .id(Id::new("AssetBrowserConfirmation"))
+        .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(warning.as_str());
+            ui.horizontal(|ui| {
+                confirmed = ui.button("Continue").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+    match (confirmed, cancelled) {
+        (true, _) => state.pending.take().map(|(operations, _)| operations),
+        (_, true) => {
+            state.pending = None;
+            None
+        }
+        _ => None,
+    }
+}
+
 /// System drawing the asset browser. Contains mostly layout and commands.
 /// Uses helper functions (draw_assets, draw_directory_hierarchy) and draw for detailed drawings
+#[allow(clippy::too_many_arguments)]
 pub fn asset_browser_system(
     mut egui_context: ResMut<EguiContext>,
-    settings: ResMut<AssetBrowserSettings>,
+    mut settings: ResMut<AssetBrowserSettings>,
     root_directory: ResMut<AssetDirectory>,
     currently_selected_directory: Res<SelectedDirectory>,
     editor_assets: Res<EditorAssets>,
-    mut select_directory_event_writer: EventWriter<EnterDirectoryCommand>,
+    asset_types: Res<AssetTypeRegistry>,
+    models: Res<Assets<Gltf>>,
+    graph: Res<AssetDependencyGraph>,
+    mut state: ResMut<AssetBrowserState>,
+    mut preview: ResMut<AssetPreview>,
+    mut dependency_view: ResMut<DependencyView>,
+    mut command_writer: CommandWriter,
+    mut file_operation_writers: FileOperationWriters,
+    mut selection: AssetSelection,
+    mut action_reader: EventReader<EditorActionEvent>,
 ) {
     let ctx = egui_context.ctx_mut();
     let current_style = (*ctx.style()).clone();
@@ -259,79 +1063,396 @@ pub fn asset_browser_system(
     new_style.visuals.button_frame = false;
     ctx.set_style(new_style);
 
+    let mut action: Option<AssetAction> = None;
+    let mut drop_target: Option<PathBuf> = None;
+    let mut grid: Vec<(PathBuf, Rect)> = Vec::new();
+    if root_directory.is_changed() {
+        state.search.invalidate();
+        state.disk_details.invalidate();
+        state.table_rows.invalidate();
+    }
+    // Search is borrowed mutably for caching while the rest of the state is drawn
+    let mut search = std::mem::take(&mut state.search);
     let bottom_panel = TopBottomPanel::bottom("ContentBrowserPanel")
         .default_height(settings.default_height)
         .resizable(true);
-    bottom_panel.show(ctx, |ui| {
-        let tree_selection =
-            draw_side_panel_tree_view(ui, &root_directory, settings.directory_hierarchy_widht);
-        if let Some(Selection::Directory(selected_dir)) = tree_selection {
-            let select_command = EnterDirectoryCommand {
-                new_selected_directory: selected_dir,
-                previous_selected_directory: currently_selected_directory.details.get_path(),
-            };
-            select_directory_event_writer.send(select_command);
+    let panel_response = bottom_panel.show(ctx, |ui| {
+        let tree_selection = draw_side_panel_tree_view(
+            ui,
+            &root_directory,
+            settings.directory_hierarchy_widht,
+            &state,
+            &mut action,
+            &mut drop_target,
+        );
+        match tree_selection {
+            Some(Selection::Directory(selected_dir)) => {
+                search.clear();
+                if let Some(select_command) =
+                    EnterDirectoryCommand::new(&currently_selected_directory, selected_dir)
+                {
+                    command_writer.send(select_command);
+                }
+            }
+            Some(Selection::Asset(asset)) => {
+                selection.select(SelectedAssets::single(asset.get_path()));
+                action = Some(AssetAction::Preview(asset));
+            }
+            None => {}
         }
 
+        ui.horizontal(|ui| {
+            let selected_path = currently_selected_directory.get_path();
+            let navigation = &mut state.navigation;
+            if let Some(navigation_action) =
+                draw_navigation_bar(ui, &root_directory, &selected_path, navigation)
+            {
+                action = Some(navigation_action);
+            }
+        });
+        ui.horizontal(|ui| {
+            draw_search_bar(ui, &mut search, &root_directory);
+            draw_view_controls(ui, &mut settings);
+        });
         let vertical_scroll_area = ScrollArea::vertical().auto_shrink([false, false]);
         vertical_scroll_area.show(ui, |ui| {
-            if let Some(selected_path) = draw_assets(
-                ui,
-                settings.thumbnails_per_row,
-                &currently_selected_directory.details,
-                &editor_assets,
-            ) {
-                let select_command = EnterDirectoryCommand {
-                    new_selected_directory: selected_path,
-                    previous_selected_directory: currently_selected_directory.get_path(),
-                };
-                select_directory_event_writer.send(select_command);
+            let assets_action = match (settings.view_mode, search.is_active()) {
+                (AssetViewMode::List, searching) => {
+                    let directory = &currently_selected_directory.details;
+                    let AssetBrowserState {
+                        disk_details,
+                        table_rows: rows,
+                        ..
+                    } = &mut *state;
+                    rows.update(
+                        searching.then(|| search.key()),
+                        settings.sort,
+                        &directory.path,
+                        || {
+                            let results = searching.then(|| search.results(&root_directory));
+                            table_rows(directory, results, disk_details)
+                        },
+                    );
+                    draw_asset_table(
+                        ui,
+                        state.table_rows.rows(),
+                        &root_directory,
+                        searching,
+                        &mut settings.sort,
+                        &state,
+                        &selection.selected,
+                        &mut drop_target,
+                        &mut grid,
+                    )
+                }
+                (AssetViewMode::Grid, true) => draw_search_results(
+                    ui,
+                    settings.thumbnail_size,
+                    &search.results(&root_directory),
+                    &editor_assets,
+                    &asset_types,
+                    &state,
+                    &selection.selected,
+                    &mut drop_target,
+                    &mut grid,
+                ),
+                (AssetViewMode::Grid, false) => draw_assets(
+                    ui,
+                    settings.thumbnail_size,
+                    &currently_selected_directory.details,
+                    &editor_assets,
+                    &asset_types,
+                    &models,
+                    &state,
+                    &selection.selected,
+                    &mut drop_target,
+                    &mut grid,
+                ),
             };
+            if assets_action.is_some() {
+                action = assets_action;
+            }
+            if let Some(band_action) = draw_rubber_band(ui, &mut state, &selection.selected, &grid)
+            {
+                action = Some(band_action);
+            }
         })
     });
     ctx.set_style(current_style);
-}
+    state.search = search;
 
-/// System for ResMut<SelectedDirectory> manipulation, with support for Undo and Redo events sent by
-/// commands system
-pub fn select_directory_system(
-    mut normal_reader: EventReader<EnterDirectoryCommand>,
-    mut undo_redo_reader: EventReader<UndoRedoCommandEvent>,
-    mut selected_directory: ResMut<SelectedDirectory>,
-    mut command_executed_writer: EventWriter<ExecuteCommandEvent>,
-    root_directory: Res<AssetDirectory>,
-) {
-    for event in normal_reader.iter() {
-        let new_selection_path = &event.new_selected_directory;
-        if selected_directory.get_path() != *new_selection_path {
-            println!("path: {:?}", new_selection_path);
-            *selected_directory = root_directory
-                .find_by_path(new_selection_path)
-                .expect("Selected Directory should contain valid path")
-                .into();
-
-            command_executed_writer.send(ExecuteCommandEvent {
-                inner: event.recreate(),
-            });
+    let entries: Vec<PathBuf> = grid.iter().map(|(path, _)| path.clone()).collect();
+    let panel_hovered = ctx
+        .input()
+        .pointer
+        .hover_pos()
+        .is_some_and(|pos| panel_response.response.rect.contains(pos));
+    // Alt+Arrow is reserved for directory navigation
+    if panel_hovered && !ctx.wants_keyboard_input() && !ctx.input().modifiers.alt {
+        let input = ctx.input();
+        // Up and Down arrows move by the number of entries in the first row
+        let row = match grid.first() {
+            Some((_, first)) => grid
+                .iter()
+                .take_while(|(_, rect)| rect.top() == first.top())
+                .count() as isize,
+            None => 1,
+        };
+        let offset = [
+            (Key::ArrowLeft, -1),
+            (Key::ArrowRight, 1),
+            (Key::ArrowUp, -row),
+            (Key::ArrowDown, row),
+        ]
+        .into_iter()
+        .find(|(key, _)| input.key_pressed(*key))
+        .map(|(_, offset)| offset);
+        if let Some(offset) = offset {
+            let extend = input.modifiers.shift;
+            action = Some(AssetAction::Select(
+                selection.selected.navigate(&entries, offset, extend),
+            ));
+        }
+    }
+    // Delete and Rename act on the selection, same as the context menu of the selected entry
+    for event in action_reader.iter() {
+        let visible = selection.selected.visible(&entries);
+        let target = selection
+            .selected
+            .lead
+            .clone()
+            .filter(|lead| visible.contains(lead))
+            .or_else(|| visible.first().cloned());
+        match (event.action, target) {
+            (EditorAction::Delete, Some(path)) if panel_hovered => {
+                action = Some(AssetAction::File(vec![FileOperation::Delete(path)]));
+            }
+            (EditorAction::Rename, Some(path)) if panel_hovered => {
+                action = Some(AssetAction::Rename(path));
+            }
+            _ => {}
         }
     }
 
-    for undo_redo_event in undo_redo_reader.iter() {
-        if undo_redo_event.cmd_type() != TypeId::of::<EnterDirectoryCommand>() {
-            continue;
+    if !state.dragged.is_empty() {
+        let label = match state.dragged.as_slice() {
+            [dragged] => dragged
+                .file_name()
+                .unwrap_or(dragged.as_os_str())
+                .to_string_lossy()
+                .to_string(),
+            dragged => format!("{} items", dragged.len()),
+        };
+        show_tooltip_text(ctx, Id::new("AssetBrowserDrag"), label.as_str());
+        if ctx.input().pointer.any_released() {
+            let dragged = std::mem::take(&mut state.dragged);
+            if let Some(directory) = drop_target {
+                let operations = drop_operations(&directory, &dragged);
+                if !operations.is_empty() {
+                    action = Some(AssetAction::File(operations));
+                }
+            }
         }
+    }
+    if let Some(operation) = draw_rename_dialog(ctx, &mut state) {
+        action = Some(AssetAction::File(vec![operation]));
+    }
+    if let Some(operations) = draw_confirmation_dialog(ctx, &mut state) {
+        file_operation_writers.send_all(operations);
+    }
 
-        let selected_directory_command: &EnterDirectoryCommand =
-            undo_redo_event.inner.as_any().downcast_ref().unwrap();
-        let new_dir = match undo_redo_event.mode {
-            CommandExecuteDirection::Redo => &selected_directory_command.new_selected_directory,
-            CommandExecuteDirection::Undo => {
-                &selected_directory_command.previous_selected_directory
+    match action {
+        Some(AssetAction::EnterDirectory(selected_path)) => {
+            state.search.clear();
+            if let Some(select_command) =
+                EnterDirectoryCommand::new(&currently_selected_directory, selected_path)
+            {
+                command_writer.send(select_command);
             }
-        };
-        *selected_directory = root_directory
-            .find_by_path(new_dir)
-            .expect("Undo/Redo should contain valid path")
-            .into();
+        }
+        Some(AssetAction::ShowInFolder(path)) => {
+            state.search.clear();
+            let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            if let Some(select_command) =
+                EnterDirectoryCommand::new(&currently_selected_directory, parent)
+            {
+                command_writer.send(select_command);
+            }
+            selection.select(SelectedAssets::single(path));
+        }
+        Some(AssetAction::Preview(asset)) => preview.show(asset),
+        Some(AssetAction::Click { path, modifiers }) => {
+            selection.select(selection.selected.click(&entries, &path, modifiers));
+            if modifiers == SelectionModifiers::default() {
+                match root_directory.find_asset(&path) {
+                    Some(AssetType::Model(model)) => state.toggle_model(model.path.clone()),
+                    Some(asset) => preview.show(asset.clone()),
+                    None => {}
+                }
+            }
+        }
+        Some(AssetAction::Select(new)) => selection.select(new),
+        Some(AssetAction::InstantiateModel { model, scene_label }) => {
+            command_writer.send(InstantiateModelCommand::new(model, scene_label));
+        }
+        Some(AssetAction::FindReferences(path)) => {
+            dependency_view.show(DependencyViewKind::References, path)
+        }
+        Some(AssetAction::ShowDependencies(path)) => {
+            dependency_view.show(DependencyViewKind::Dependencies, path)
+        }
+        Some(AssetAction::Rename(path)) => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            state.renaming = Some((path.clone(), name.to_string()));
+        }
+        Some(AssetAction::Drag(path)) => {
+            state.dragged = match selection.selected.contains(&path) {
+                true => selection.selected.visible(&entries),
+                false => vec![path],
+            };
+        }
+        Some(AssetAction::File(operations)) => {
+            let selected = selection.selected.visible(&entries);
+            let operations: Vec<FileOperation> = operations
+                .into_iter()
+                .flat_map(|operation| expand_to_selection(operation, &selected))
+                .collect();
+            // Operations breaking references have to be confirmed first
+            let affected: Vec<PathBuf> = operations
+                .iter()
+                .flat_map(|operation| operation.affected_paths().to_vec())
+                .collect();
+            match graph.reference_warning(&affected) {
+                Some(warning) => state.pending = Some((operations, warning)),
+                None => file_operation_writers.send_all(operations),
+            }
+        }
+        None => {}
+    }
+}
+
+/// Selects entries of the grid by dragging a rectangle over the empty space.
+/// Holding Ctrl or Shift adds the entries to the current selection
+fn draw_rubber_band(
+    ui: &mut Ui,
+    state: &mut AssetBrowserState,
+    selected: &SelectedAssets,
+    grid: &[(PathBuf, Rect)],
+) -> Option<AssetAction> {
+    let background = ui.interact(
+        ui.clip_rect(),
+        Id::new("AssetBrowserRubberBand"),
+        Sense::click_and_drag(),
+    );
+    if background.drag_started() {
+        state.rubber_band = background.interact_pointer_pos();
+    }
+    let origin = state.rubber_band?;
+    let pointer = ui.input().pointer.interact_pos().unwrap_or(origin);
+    let band = Rect::from_two_pos(origin, pointer);
+    ui.painter().rect(
+        band,
+        0.0,
+        ui.visuals().selection.bg_fill.linear_multiply(0.3),
+        ui.visuals().selection.stroke,
+    );
+    if !background.drag_released() {
+        return None;
+    }
+    state.rubber_band = None;
+    let hit = grid
+        .iter()
+        .filter(|(_, rect)| rect.intersects(band))
+        .map(|(path, _)| path.clone())
+        .collect();
+    let modifiers = ui.input().modifiers;
+    let additive = modifiers.command || modifiers.shift;
+    Some(AssetAction::Select(selected.rubber_band(hit, additive)))
+}
+
+fn missing_directory_error(path: &Path) -> CommandError {
+    CommandError::Other(format!("Directory {:?} doesn't exist", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::testing::CommandTestApp;
+
+    fn enter(app: &mut CommandTestApp, directory: &str) {
+        let selected = app.resource::<SelectedDirectory>();
+        let command = EnterDirectoryCommand::new(selected, app.path(directory)).unwrap();
+        app.send(command);
+    }
+
+    #[test]
+    fn entering_directories_merges_into_one_step() {
+        let mut app = CommandTestApp::new().with_asset_directories(&["textures", "scenes"]);
+        enter(&mut app, "textures");
+        enter(&mut app, "scenes");
+        assert_eq!(app.queue().items.len(), 1);
+        assert_eq!(
+            app.resource::<SelectedDirectory>().get_path(),
+            app.path("scenes")
+        );
+
+        app.undo();
+        assert_eq!(app.resource::<SelectedDirectory>().get_path(), app.path(""));
+    }
+
+    #[test]
+    fn entering_selected_directory_is_ignored() {
+        let app = CommandTestApp::new().with_asset_directories(&[]);
+        let selected = app.resource::<SelectedDirectory>();
+        assert!(EnterDirectoryCommand::new(selected, app.path("")).is_none());
+    }
+
+    #[test]
+    fn selected_directory_follows_moves_and_removals() {
+        let mut app = CommandTestApp::new().with_asset_directories(&["textures"]);
+        app.app
+            .add_event::<AssetAddedEvent>()
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
+            .add_system(refresh_selected_directory_system);
+        enter(&mut app, "textures");
+
+        let (from, to) = (app.path("textures"), app.path("materials"));
+        std::fs::rename(&from, &to).unwrap();
+        let mut root = app.app.world.resource_mut::<AssetDirectory>();
+        root.remove_path(&from);
+        root.insert_path(&to, &mut |_| None);
+        app.app.world.send_event(AssetMovedEvent {
+            from,
+            to,
+            from_command: false,
+        });
+        app.update();
+        assert_eq!(
+            app.resource::<SelectedDirectory>().get_path(),
+            app.path("materials")
+        );
+
+        let path = app.path("materials");
+        app.app
+            .world
+            .resource_mut::<AssetDirectory>()
+            .remove_path(&path);
+        app.app.world.send_event(AssetRemovedEvent {
+            path,
+            from_command: false,
+        });
+        app.update();
+        assert_eq!(app.resource::<SelectedDirectory>().get_path(), app.path(""));
+    }
+
+    #[test]
+    fn entering_missing_directory_fails() {
+        let mut app = CommandTestApp::new().with_asset_directories(&[]);
+        let selected = app.resource::<SelectedDirectory>();
+        let command = EnterDirectoryCommand::new(selected, app.path("missing")).unwrap();
+        app.send(command);
+        assert!(app.queue().items.is_empty());
+        assert_eq!(app.drain_failures().len(), 1);
     }
 }
@@ -0,0 +1,228 @@
+use crate::editor::assets::asset_loader::AssetDirectory;
+use crate::editor::commands::dispatch::CommandWriter;
+use crate::editor::keymap::{EditorAction, EditorActionEvent};
+use crate::editor::ui::asset_browser::{
+    AssetBrowserState, EnterDirectoryCommand, SelectedDirectory,
+};
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Navigation between directories of the asset browser, independent of the undo history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Back,
+    Forward,
+    Up,
+}
+
+/// Back and forward history of directories displayed in the asset browser
+#[derive(Debug, Default)]
+pub struct DirectoryHistory {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+    current: Option<PathBuf>,
+    /// Back or forward navigation requested, but not yet displayed
+    pending: Option<Navigation>,
+}
+
+impl DirectoryHistory {
+    /// Record the displayed directory. Entering a new directory clears the forward history,
+    /// unless it is the target of the pending back or forward navigation
+    pub fn visit(&mut self, directory: &Path) {
+        let pending = self.pending.take();
+        if directory.as_os_str().is_empty() || self.current.as_deref() == Some(directory) {
+            return;
+        }
+        let previous = match self.current.replace(directory.to_path_buf()) {
+            Some(previous) => previous,
+            None => return,
+        };
+        let reached = |from: &Vec<PathBuf>| from.last().map(PathBuf::as_path) == Some(directory);
+        match pending {
+            Some(Navigation::Back) if reached(&self.back) => {
+                self.back.pop();
+                self.forward.push(previous);
+            }
+            Some(Navigation::Forward) if reached(&self.forward) => {
+                self.forward.pop();
+                self.back.push(previous);
+            }
+            _ => {
+                self.back.push(previous);
+                self.forward.clear();
+            }
+        }
+    }
+
+    pub fn can_navigate(&self, navigation: Navigation, root: &AssetDirectory) -> bool {
+        match navigation {
+            Navigation::Back => !self.back.is_empty(),
+            Navigation::Forward => !self.forward.is_empty(),
+            Navigation::Up => self.parent(root).is_some(),
+        }
+    }
+
+    /// Directory to enter. Back and forward skip directories removed in the meantime,
+    /// the history moves once the directory is visited
+    pub fn navigate(&mut self, navigation: Navigation, root: &AssetDirectory) -> Option<PathBuf> {
+        let from = match navigation {
+            Navigation::Back => &mut self.back,
+            Navigation::Forward => &mut self.forward,
+            Navigation::Up => return self.parent(root),
+        };
+        while root.find_by_path(from.last()?).is_none() {
+            from.pop();
+        }
+        self.pending = Some(navigation);
+        from.last().cloned()
+    }
+
+    fn parent(&self, root: &AssetDirectory) -> Option<PathBuf> {
+        let current = self.current.as_ref()?;
+        match current != &root.path && current.starts_with(&root.path) {
+            true => current.parent().map(Path::to_path_buf),
+            false => None,
+        }
+    }
+}
+
+/// Segments of the path from the root to the directory, each with the directory it enters
+pub fn breadcrumbs(root: &AssetDirectory, directory: &Path) -> Vec<(String, PathBuf)> {
+    let mut path = root.path.clone();
+    let mut crumbs = vec![(root.get_name(), path.clone())];
+    if let Ok(relative) = directory.strip_prefix(&root.path) {
+        for component in relative.components() {
+            path.push(component);
+            let name = component.as_os_str().to_string_lossy().to_string();
+            crumbs.push((name, path.clone()));
+        }
+    }
+    crumbs
+}
+
+/// Mouse back and forward buttons. winit reports them with platform specific numbers,
+/// X11 button numbers and Wayland BTN_SIDE and BTN_EXTRA codes on Linux
+fn mouse_navigation_buttons() -> (&'static [MouseButton], &'static [MouseButton]) {
+    if cfg!(target_os = "windows") {
+        (&[MouseButton::Other(1)], &[MouseButton::Other(2)])
+    } else if cfg!(target_os = "macos") {
+        (&[MouseButton::Other(3)], &[MouseButton::Other(4)])
+    } else {
+        (
+            &[MouseButton::Other(8), MouseButton::Other(0x113)],
+            &[MouseButton::Other(9), MouseButton::Other(0x114)],
+        )
+    }
+}
+
+/// Records visited directories and enters directories requested by keymap actions
+/// and mouse back and forward buttons
+pub fn directory_navigation_system(
+    mouse: Res<Input<MouseButton>>,
+    root_directory: Res<AssetDirectory>,
+    selected_directory: Res<SelectedDirectory>,
+    mut state: ResMut<AssetBrowserState>,
+    mut action_reader: EventReader<EditorActionEvent>,
+    mut command_writer: CommandWriter,
+) {
+    state.navigation.visit(&selected_directory.get_path());
+
+    let (back_buttons, forward_buttons) = mouse_navigation_buttons();
+    let mut navigation = None;
+    for event in action_reader.iter() {
+        navigation = match event.action {
+            EditorAction::NavigateBack => Some(Navigation::Back),
+            EditorAction::NavigateForward => Some(Navigation::Forward),
+            EditorAction::NavigateUp => Some(Navigation::Up),
+            _ => navigation,
+        };
+    }
+    if mouse.any_just_pressed(back_buttons.iter().copied()) {
+        navigation = Some(Navigation::Back);
+    }
+    if mouse.any_just_pressed(forward_buttons.iter().copied()) {
+        navigation = Some(Navigation::Forward);
+    }
+
+    let target =
+        navigation.and_then(|navigation| state.navigation.navigate(navigation, &root_directory));
+    if let Some(command) =
+        target.and_then(|target| EnterDirectoryCommand::new(&selected_directory, target))
+    {
+        command_writer.send(command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root() -> AssetDirectory {
+        let mut textures = AssetDirectory::new(PathBuf::from("/assets/textures"));
+        let grass = AssetDirectory::new(PathBuf::from("/assets/textures/grass"));
+        textures.children_directories.push(grass);
+        let mut root = AssetDirectory::new(PathBuf::from("/assets"));
+        root.children_directories.push(textures);
+        root
+    }
+
+    #[test]
+    fn history_goes_back_and_forward() {
+        let root = root();
+        let mut history = DirectoryHistory::default();
+        for directory in ["/assets", "/assets/textures", "/assets/textures/grass"] {
+            history.visit(Path::new(directory));
+        }
+        assert!(!history.can_navigate(Navigation::Forward, &root));
+
+        let back = history.navigate(Navigation::Back, &root);
+        assert_eq!(back, Some(PathBuf::from("/assets/textures")));
+        history.visit(Path::new("/assets/textures"));
+        let forward = history.navigate(Navigation::Forward, &root);
+        assert_eq!(forward, Some(PathBuf::from("/assets/textures/grass")));
+        history.visit(Path::new("/assets/textures/grass"));
+        let up = history.navigate(Navigation::Up, &root);
+        assert_eq!(up, Some(PathBuf::from("/assets/textures")));
+        history.visit(Path::new("/assets/textures"));
+
+        history.navigate(Navigation::Back, &root);
+        history.visit(Path::new("/assets/textures/grass"));
+        history.navigate(Navigation::Back, &root);
+        history.visit(Path::new("/assets/textures"));
+        history.navigate(Navigation::Back, &root);
+        history.visit(Path::new("/assets"));
+        assert!(!history.can_navigate(Navigation::Back, &root));
+        assert!(!history.can_navigate(Navigation::Up, &root));
+        let forward = history.navigate(Navigation::Forward, &root);
+        assert_eq!(forward, Some(PathBuf::from("/assets/textures")));
+    }
+
+    #[test]
+    fn history_waits_for_the_directory_to_be_visited() {
+        let root = root();
+        let mut history = DirectoryHistory::default();
+        for directory in ["/assets", "/assets/textures"] {
+            history.visit(Path::new(directory));
+        }
+
+        // Entering the directory failed, the selection didn't change
+        history.navigate(Navigation::Back, &root);
+        history.visit(Path::new("/assets/textures"));
+        assert!(history.can_navigate(Navigation::Back, &root));
+        assert!(!history.can_navigate(Navigation::Forward, &root));
+
+        let back = history.navigate(Navigation::Back, &root);
+        assert_eq!(back, Some(PathBuf::from("/assets")));
+        history.visit(Path::new("/assets"));
+        assert!(!history.can_navigate(Navigation::Back, &root));
+        assert!(history.can_navigate(Navigation::Forward, &root));
+    }
+
+    #[test]
+    fn breadcrumbs_start_at_root() {
+        let crumbs = breadcrumbs(&root(), Path::new("/assets/textures/grass"));
+        let names: Vec<&str> = crumbs.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["assets", "textures", "grass"]);
+        assert_eq!(crumbs[1].1, PathBuf::from("/assets/textures"));
+    }
+}
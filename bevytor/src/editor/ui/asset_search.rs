@@ -0,0 +1,240 @@
+use crate::editor::assets::asset_loader::{AssetDirectory, AssetType};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Entry of the asset browser search index
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    pub path: PathBuf,
+    /// Asset itself, None for directories
+    pub asset: Option<AssetType>,
+    /// Containing directory starting with the root name, displayed next to the result
+    pub folder: String,
+    /// Lowercase name used for matching
+    name: String,
+}
+
+/// Lowercase query and type names the results are computed for
+pub type SearchKey = (String, BTreeSet<&'static str>);
+
+/// Recursive search over the whole AssetDirectory by name and asset type.
+/// Hierarchy is flattened into an index once per change, results are cached per query
+#[derive(Debug, Default)]
+pub struct AssetSearch {
+    /// Substring, or glob pattern if it contains any of `*?[{`
+    pub query: String,
+    /// Type names of the enabled type chips. No enabled chip means all types and directories
+    pub types: BTreeSet<&'static str>,
+    index: Option<Vec<SearchEntry>>,
+    /// Query and types the results were computed for
+    cached: Option<SearchKey>,
+    /// Indices of the matching index entries
+    results: Vec<usize>,
+}
+
+impl AssetSearch {
+    /// Search is active as soon as there is a query or an enabled type chip
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty() || !self.types.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.types.clear();
+    }
+
+    pub fn toggle_type(&mut self, type_name: &'static str) {
+        if !self.types.remove(type_name) {
+            self.types.insert(type_name);
+        }
+    }
+
+    /// Drop the index, e.g. after the hierarchy changed
+    pub fn invalidate(&mut self) {
+        self.index = None;
+        self.cached = None;
+    }
+
+    /// Type names of all indexed assets, used as type chips
+    pub fn type_names(&mut self, root: &AssetDirectory) -> BTreeSet<&'static str> {
+        self.index(root)
+            .iter()
+            .filter_map(|entry| entry.asset.as_ref().map(AssetType::type_name))
+            .collect()
+    }
+
+    /// Identifies the results, changes whenever the query or types do
+    pub fn key(&self) -> SearchKey {
+        (self.query.trim().to_lowercase(), self.types.clone())
+    }
+
+    /// Entries matching the query and types, in hierarchy order
+    pub fn results(&mut self, root: &AssetDirectory) -> Vec<&SearchEntry> {
+        let key = self.key();
+        if self.cached.as_ref() != Some(&key) {
+            let pattern = Pattern::new(&key.0);
+            let types = &key.1;
+            self.results = self
+                .index(root)
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| match (&entry.asset, types.is_empty()) {
+                    (_, true) => true,
+                    (Some(asset), false) => types.contains(asset.type_name()),
+                    (None, false) => false,
+                })
+                .filter(|(_, entry)| pattern.matches(&entry.name))
+                .map(|(index, _)| index)
+                .collect();
+            self.cached = Some(key);
+        }
+        let index = self
+            .index
+            .as_ref()
+            .expect("Search index is built with results");
+        self.results.iter().map(|&result| &index[result]).collect()
+    }
+
+    fn index(&mut self, root: &AssetDirectory) -> &[SearchEntry] {
+        self.index.get_or_insert_with(|| {
+            let mut index = Vec::new();
+            build_index(root, &root.path, &mut index);
+            index
+        })
+    }
+}
+
+fn build_index(directory: &AssetDirectory, root: &Path, index: &mut Vec<SearchEntry>) {
+    let relative = directory.path.strip_prefix(root).unwrap_or(&directory.path);
+    let folder = Path::new(root.file_name().unwrap_or_default())
+        .join(relative)
+        .to_string_lossy()
+        .to_string();
+    for child in directory.children_directories.iter() {
+        index.push(SearchEntry {
+            path: child.path.clone(),
+            asset: None,
+            folder: folder.clone(),
+            name: child.name.to_string_lossy().to_lowercase(),
+        });
+    }
+    for asset in directory.assets.iter() {
+        index.push(SearchEntry {
+            path: asset.get_path(),
+            asset: Some(asset.clone()),
+            folder: folder.clone(),
+            name: asset.get_name().to_lowercase(),
+        });
+    }
+    for child in directory.children_directories.iter() {
+        build_index(child, root, index);
+    }
+}
+
+/// Lowercase query matched against lowercase names
+enum Pattern {
+    Substring(String),
+    Glob(GlobMatcher),
+}
+
+impl Pattern {
+    /// Invalid glob, e.g. unclosed `[`, falls back to substring
+    fn new(query: &str) -> Self {
+        let glob = query
+            .contains(['*', '?', '[', '{'])
+            .then(|| GlobBuilder::new(query).literal_separator(false).build())
+            .and_then(Result::ok);
+        match glob {
+            Some(glob) => Pattern::Glob(glob.compile_matcher()),
+            None => Pattern::Substring(query.to_string()),
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Substring(substring) => name.contains(substring.as_str()),
+            Pattern::Glob(glob) => glob.is_match(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::assets::asset_loader::GenericAssetDescriptor;
+
+    fn root() -> AssetDirectory {
+        let mut textures = AssetDirectory::new(PathBuf::from("/assets/textures"));
+        for (name, type_name) in [("Grass.png", "Image"), ("grass_normal.png", "Image")] {
+            let path = textures.path.join(name);
+            let asset = GenericAssetDescriptor::new(&path, type_name);
+            textures.assets.push(AssetType::Other(asset));
+        }
+        let mut root = AssetDirectory::new(PathBuf::from("/assets"));
+        let level = GenericAssetDescriptor::new(Path::new("/assets/grass.scn.ron"), "Scene");
+        root.assets.push(AssetType::Other(level));
+        root.children_directories.push(textures);
+        root
+    }
+
+    fn names(results: Vec<&SearchEntry>) -> Vec<String> {
+        results
+            .iter()
+            .map(|entry| {
+                entry
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn search_matches_names_recursively() {
+        let root = root();
+        let mut search = AssetSearch::default();
+        assert!(!search.is_active());
+
+        search.query = "GRASS".into();
+        let results = search.results(&root);
+        assert_eq!(results[1].folder, "assets/textures");
+        assert_eq!(
+            names(results),
+            ["grass.scn.ron", "Grass.png", "grass_normal.png"]
+        );
+
+        search.query = "*.png".into();
+        assert_eq!(
+            names(search.results(&root)),
+            ["Grass.png", "grass_normal.png"]
+        );
+        search.query = "tex".into();
+        assert_eq!(names(search.results(&root)), ["textures"]);
+
+        search.query = "grass".into();
+        search.toggle_type("Scene");
+        assert_eq!(names(search.results(&root)), ["grass.scn.ron"]);
+        assert_eq!(
+            search.type_names(&root).into_iter().collect::<Vec<_>>(),
+            ["Image", "Scene"]
+        );
+    }
+
+    #[test]
+    fn results_are_cached_until_invalidated() {
+        let mut root = root();
+        let mut search = AssetSearch {
+            query: "grass".into(),
+            ..Default::default()
+        };
+        assert_eq!(search.results(&root).len(), 3);
+
+        root.assets.clear();
+        assert_eq!(search.results(&root).len(), 3);
+        search.invalidate();
+        assert_eq!(search.results(&root).len(), 2);
+    }
+}
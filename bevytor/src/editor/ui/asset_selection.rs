@@ -0,0 +1,311 @@
+use crate::editor::commands::dispatch::{ApplyCommand, CommandWriter};
+use crate::editor::commands::{Command, CommandAny, CommandError};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::path::{Path, PathBuf};
+
+/// Modifiers changing the meaning of a click on the thumbnail
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectionModifiers {
+    /// Ctrl (Cmd on macOS), adds the entry to the selection or removes it
+    pub toggle: bool,
+    /// Shift, selects the range starting at the anchor
+    pub extend: bool,
+}
+
+/// Resource with assets and directories selected in the asset browser.
+/// Paths are compared against the entries of the selected directory, entries of other
+/// directories are never displayed as selected
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectedAssets {
+    /// Selected entries in the order of selection
+    pub paths: Vec<PathBuf>,
+    /// Entry from which Shift ranges start
+    pub anchor: Option<PathBuf>,
+    /// Entry moved by the arrow keys, the most recently clicked one
+    pub lead: Option<PathBuf>,
+}
+
+impl SelectedAssets {
+    pub fn single(path: PathBuf) -> Self {
+        Self {
+            paths: vec![path.clone()],
+            anchor: Some(path.clone()),
+            lead: Some(path),
+        }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|selected| selected == path)
+    }
+
+    /// Selected entries among the given ones, in their order
+    pub fn visible(&self, entries: &[PathBuf]) -> Vec<PathBuf> {
+        entries
+            .iter()
+            .filter(|entry| self.contains(entry))
+            .cloned()
+            .collect()
+    }
+
+    /// Selection after clicking the entry. `entries` are all the entries in displayed order
+    pub fn click(&self, entries: &[PathBuf], path: &Path, modifiers: SelectionModifiers) -> Self {
+        if modifiers.extend {
+            let anchor = self
+                .anchor
+                .clone()
+                .filter(|anchor| entries.contains(anchor))
+                .unwrap_or_else(|| path.to_path_buf());
+            let mut paths = match modifiers.toggle {
+                true => self.paths.clone(),
+                false => Vec::new(),
+            };
+            for entry in range(entries, &anchor, path) {
+                if !paths.contains(&entry) {
+                    paths.push(entry);
+                }
+            }
+            return Self {
+                paths,
+                anchor: Some(anchor),
+                lead: Some(path.to_path_buf()),
+            };
+        }
+
+        if modifiers.toggle {
+            let mut paths = self.paths.clone();
+            match paths.iter().position(|selected| selected == path) {
+                Some(index) => {
+                    paths.remove(index);
+                }
+                None => paths.push(path.to_path_buf()),
+            }
+            return Self {
+                paths,
+                anchor: Some(path.to_path_buf()),
+                lead: Some(path.to_path_buf()),
+            };
+        }
+
+        Self::single(path.to_path_buf())
+    }
+
+    /// Selection after dragging rubber band over `hit` entries. Additive band keeps
+    /// the current selection
+    pub fn rubber_band(&self, hit: Vec<PathBuf>, additive: bool) -> Self {
+        let mut paths = match additive {
+            true => self.paths.clone(),
+            false => Vec::new(),
+        };
+        for entry in hit.iter() {
+            if !paths.contains(entry) {
+                paths.push(entry.clone());
+            }
+        }
+        Self {
+            paths,
+            anchor: hit.first().cloned().or_else(|| self.anchor.clone()),
+            lead: hit.last().cloned().or_else(|| self.lead.clone()),
+        }
+    }
+
+    /// Selection after moving the lead by `offset` entries, e.g. by the whole row for Up and
+    /// Down arrows. Extended selection keeps the anchor and selects the range
+    pub fn navigate(&self, entries: &[PathBuf], offset: isize, extend: bool) -> Self {
+        if entries.is_empty() {
+            return self.clone();
+        }
+        let target = match self
+            .lead
+            .as_ref()
+            .and_then(|lead| entries.iter().position(|entry| entry == lead))
+        {
+            Some(index) => (index as isize + offset).clamp(0, entries.len() as isize - 1) as usize,
+            None => 0,
+        };
+        let modifiers = SelectionModifiers {
+            toggle: false,
+            extend,
+        };
+        self.click(entries, &entries[target], modifiers)
+    }
+}
+
+/// Entries between `from` and `to`, both included. Only `to` if any of them is missing
+fn range(entries: &[PathBuf], from: &Path, to: &Path) -> Vec<PathBuf> {
+    let position = |path: &Path| entries.iter().position(|entry| entry == path);
+    match (position(from), position(to)) {
+        (Some(from), Some(to)) => entries[from.min(to)..=from.max(to)].to_vec(),
+        _ => vec![to.to_path_buf()],
+    }
+}
+
+/// Command changing SelectedAssets. Consecutive changes, e.g. walking with arrow keys,
+/// are merged into a single undo step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectAssetsCommand {
+    pub previous: SelectedAssets,
+    pub new: SelectedAssets,
+}
+
+impl SelectAssetsCommand {
+    /// Create command changing the selection. Returns None if nothing changes
+    pub fn new(selected: &SelectedAssets, new: SelectedAssets) -> Option<Self> {
+        (*selected != new).then(|| Self {
+            previous: selected.clone(),
+            new,
+        })
+    }
+}
+
+impl Command for SelectAssetsCommand {
+    fn recreate(&self) -> Box<dyn CommandAny> {
+        Box::new(self.clone())
+    }
+
+    fn command_type(&self) -> TypeId {
+        TypeId::of::<SelectAssetsCommand>()
+    }
+
+    fn merge(&mut self, next: &dyn CommandAny) -> bool {
+        match next.as_any().downcast_ref::<SelectAssetsCommand>() {
+            Some(next) if next.previous == self.new => {
+                self.new = next.new.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn estimated_size(&self) -> usize {
+        let paths_size = |selection: &SelectedAssets| {
+            selection
+                .paths
+                .iter()
+                .chain(selection.anchor.iter())
+                .chain(selection.lead.iter())
+                .map(|path| std::mem::size_of_val(path) + path.as_os_str().len())
+                .sum::<usize>()
+        };
+        std::mem::size_of_val(self) + paths_size(&self.previous) + paths_size(&self.new)
+    }
+
+    fn affected_resources(&self) -> Vec<TypeId> {
+        vec![TypeId::of::<SelectedAssets>()]
+    }
+
+    fn describe(&self) -> String {
+        match self.new.paths.as_slice() {
+            [] => "Clear selection".into(),
+            [path] => {
+                let name = path.file_name().unwrap_or(path.as_os_str());
+                format!("Select {}", name.to_string_lossy())
+            }
+            paths => format!("Select {} items", paths.len()),
+        }
+    }
+}
+
+impl ApplyCommand for SelectAssetsCommand {
+    fn apply(&self, world: &mut World) -> Result<(), CommandError> {
+        *world.resource_mut::<SelectedAssets>() = self.new.clone();
+        Ok(())
+    }
+
+    fn revert(&self, world: &mut World) -> Result<(), CommandError> {
+        *world.resource_mut::<SelectedAssets>() = self.previous.clone();
+        Ok(())
+    }
+}
+
+/// Current selection together with the writer of its changes
+#[derive(SystemParam)]
+pub struct AssetSelection<'w, 's> {
+    pub selected: Res<'w, SelectedAssets>,
+    writer: CommandWriter<'w, 's>,
+}
+
+impl<'w, 's> AssetSelection<'w, 's> {
+    /// Send SelectAssetsCommand if the selection changes
+    #[track_caller]
+    pub fn select(&mut self, new: SelectedAssets) {
+        if let Some(command) = SelectAssetsCommand::new(&self.selected, new) {
+            self.writer.send(command);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::commands::dispatch::AddEditorCommand;
+    use crate::editor::testing::CommandTestApp;
+
+    fn entries() -> Vec<PathBuf> {
+        ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn clicks_with_modifiers_select_ranges_and_toggle() {
+        let entries = entries();
+        let toggle = SelectionModifiers {
+            toggle: true,
+            extend: false,
+        };
+        let extend = SelectionModifiers {
+            toggle: false,
+            extend: true,
+        };
+
+        let selected =
+            SelectedAssets::default().click(&entries, Path::new("b"), Default::default());
+        assert_eq!(selected.paths, paths(&["b"]));
+        let selected = selected.click(&entries, Path::new("d"), extend);
+        assert_eq!(selected.paths, paths(&["b", "c", "d"]));
+        let selected = selected.click(&entries, Path::new("a"), extend);
+        assert_eq!(selected.paths, paths(&["a", "b"]));
+        let selected = selected.click(&entries, Path::new("b"), toggle);
+        assert_eq!(selected.paths, paths(&["a"]));
+        let selected = selected.click(&entries, Path::new("e"), toggle);
+        assert_eq!(selected.visible(&entries), paths(&["a", "e"]));
+
+        let selected = selected.navigate(&entries, -2, true);
+        assert_eq!(selected.lead, Some(PathBuf::from("c")));
+        assert_eq!(selected.paths, paths(&["c", "d", "e"]));
+        let selected = selected.navigate(&entries, 10, false);
+        assert_eq!(selected.paths, paths(&["e"]));
+
+        let selected = selected.rubber_band(paths(&["a", "b"]), true);
+        assert_eq!(selected.paths, paths(&["e", "a", "b"]));
+        assert!(selected.rubber_band(Vec::new(), false).paths.is_empty());
+    }
+
+    #[test]
+    fn selection_changes_are_undone_in_one_step() {
+        let mut app = CommandTestApp::new();
+        app.app
+            .init_resource::<SelectedAssets>()
+            .add_editor_command::<SelectAssetsCommand>();
+        let entries = entries();
+        let first = SelectedAssets::single(entries[0].clone());
+        app.send(SelectAssetsCommand::new(&SelectedAssets::default(), first.clone()).unwrap());
+        let second = first.navigate(&entries, 1, true);
+        app.send(SelectAssetsCommand::new(&first, second.clone()).unwrap());
+        assert_eq!(app.resource::<SelectedAssets>(), &second);
+        assert_eq!(app.queue().items.len(), 1);
+        assert_eq!(app.queue().items[0].describe(), "Select 2 items");
+
+        app.undo();
+        assert!(app.resource::<SelectedAssets>().paths.is_empty());
+        assert!(SelectAssetsCommand::new(&second, second.clone()).is_none());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::editor::assets::asset_loader::AssetType;
+use crate::editor::ui::asset_search::SearchKey;
+use bevy::utils::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How the asset browser displays entries of the selected directory or search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetViewMode {
+    Grid,
+    List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetSortColumn {
+    Name,
+    Type,
+    Size,
+    Modified,
+    Path,
+}
+
+impl AssetSortColumn {
+    pub const ALL: [AssetSortColumn; 5] = [
+        AssetSortColumn::Name,
+        AssetSortColumn::Type,
+        AssetSortColumn::Size,
+        AssetSortColumn::Modified,
+        AssetSortColumn::Path,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetSortColumn::Name => "Name",
+            AssetSortColumn::Type => "Type",
+            AssetSortColumn::Size => "Size",
+            AssetSortColumn::Modified => "Modified",
+            AssetSortColumn::Path => "Path",
+        }
+    }
+}
+
+/// Column the list view is sorted by. Directories are always listed first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetSort {
+    pub column: AssetSortColumn,
+    pub ascending: bool,
+}
+
+impl Default for AssetSort {
+    fn default() -> Self {
+        Self {
+            column: AssetSortColumn::Name,
+            ascending: true,
+        }
+    }
+}
+
+impl AssetSort {
+    /// Clicking the sorted column flips the order, other columns start ascending
+    pub fn click(&mut self, column: AssetSortColumn) {
+        match self.column == column {
+            true => self.ascending = !self.ascending,
+            false => {
+                self.column = column;
+                self.ascending = true;
+            }
+        }
+    }
+
+    pub fn sort(&self, rows: &mut [AssetRow]) {
+        rows.sort_by(|a, b| {
+            let ordering = match self.column {
+                AssetSortColumn::Name => a.sort_name.cmp(&b.sort_name),
+                AssetSortColumn::Type => a.type_name.cmp(b.type_name),
+                AssetSortColumn::Size => a.size.cmp(&b.size),
+                AssetSortColumn::Modified => a.modified.cmp(&b.modified),
+                AssetSortColumn::Path => a.path.cmp(&b.path),
+            };
+            let ordering = match self.ascending {
+                true => ordering,
+                false => ordering.reverse(),
+            };
+            b.is_directory
+                .cmp(&a.is_directory)
+                .then(ordering)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+    }
+}
+
+/// Row of the list view
+#[derive(Debug, Clone)]
+pub struct AssetRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub type_name: &'static str,
+    pub is_directory: bool,
+    /// Size on disk, None for directories
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    /// Lowercase name the Name column is sorted by
+    sort_name: String,
+}
+
+impl AssetRow {
+    pub fn directory(path: &Path, details: &mut DiskDetails) -> Self {
+        let name: String = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into();
+        Self {
+            path: path.to_path_buf(),
+            sort_name: name.to_lowercase(),
+            name,
+            type_name: "Folder",
+            is_directory: true,
+            size: None,
+            modified: details.get(path).1,
+        }
+    }
+
+    pub fn asset(asset: &AssetType, details: &mut DiskDetails) -> Self {
+        let path = asset.get_path();
+        let (size, modified) = details.get(&path);
+        let name = asset.get_name();
+        Self {
+            sort_name: name.to_lowercase(),
+            name,
+            type_name: asset.type_name(),
+            is_directory: false,
+            size,
+            modified,
+            path,
+        }
+    }
+}
+
+/// Sorted rows of the list view, rebuilt only when the search, sort or directory changes
+#[derive(Debug, Default)]
+pub struct AssetTableRows {
+    /// Search, sort and directory the rows were built for
+    cached: Option<(Option<SearchKey>, AssetSort, PathBuf)>,
+    rows: Vec<AssetRow>,
+}
+
+impl AssetTableRows {
+    /// Drop the rows, e.g. after the hierarchy changed
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// Rebuild and sort the rows if they were built for a different search, sort or directory
+    pub fn update(
+        &mut self,
+        search: Option<SearchKey>,
+        sort: AssetSort,
+        directory: &Path,
+        build: impl FnOnce() -> Vec<AssetRow>,
+    ) {
+        let key = (search, sort, directory.to_path_buf());
+        if self.cached.as_ref() != Some(&key) {
+            self.rows = build();
+            sort.sort(&mut self.rows);
+            self.cached = Some(key);
+        }
+    }
+
+    pub fn rows(&self) -> &[AssetRow] {
+        &self.rows
+    }
+}
+
+/// Cache of file sizes and modification times, so the list view doesn't hit the disk every frame
+#[derive(Debug, Default)]
+pub struct DiskDetails {
+    cache: HashMap<PathBuf, (Option<u64>, Option<SystemTime>)>,
+}
+
+impl DiskDetails {
+    /// Size and modification time of the file. Size of directories is not computed
+    pub fn get(&mut self, path: &Path) -> (Option<u64>, Option<SystemTime>) {
+        *self
+            .cache
+            .entry(path.to_path_buf())
+            .or_insert_with(|| match fs::metadata(path) {
+                Ok(metadata) => (
+                    metadata.is_file().then_some(metadata.len()),
+                    metadata.modified().ok(),
+                ),
+                Err(_) => (None, None),
+            })
+    }
+
+    pub fn invalidate(&mut self) {
+        self.cache.clear();
+    }
+}
+
+/// Human readable size, e.g. 1.5 MB
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Modification time as UTC date and time, e.g. 2022-08-14 17:05
+pub fn format_modified(modified: SystemTime) -> String {
+    let seconds = match modified.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(_) => return "-".into(),
+    };
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let days = seconds.div_euclid(86400);
+    let time = seconds.rem_euclid(86400);
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn row(name: &str, is_directory: bool, size: Option<u64>) -> AssetRow {
+        AssetRow {
+            path: PathBuf::from(name),
+            name: name.into(),
+            sort_name: name.to_lowercase(),
+            type_name: if is_directory { "Folder" } else { "Image" },
+            is_directory,
+            size,
+            modified: None,
+        }
+    }
+
+    fn names(rows: &[AssetRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.name.as_str()).collect()
+    }
+
+    #[test]
+    fn rows_are_sorted_with_directories_first() {
+        let mut rows = vec![
+            row("b.png", false, Some(10)),
+            row("textures", true, None),
+            row("A.png", false, Some(20)),
+        ];
+        let mut sort = AssetSort::default();
+        sort.sort(&mut rows);
+        assert_eq!(names(&rows), ["textures", "A.png", "b.png"]);
+
+        sort.click(AssetSortColumn::Size);
+        sort.sort(&mut rows);
+        assert_eq!(names(&rows), ["textures", "b.png", "A.png"]);
+        sort.click(AssetSortColumn::Size);
+        assert!(!sort.ascending);
+        sort.sort(&mut rows);
+        assert_eq!(names(&rows), ["textures", "A.png", "b.png"]);
+    }
+
+    #[test]
+    fn rows_are_rebuilt_when_key_changes() {
+        let mut table = AssetTableRows::default();
+        let mut builds = 0;
+        let mut update = |table: &mut AssetTableRows, sort: AssetSort, directory: &str| {
+            table.update(None, sort, Path::new(directory), || {
+                builds += 1;
+                vec![row("b.png", false, Some(10)), row("A.png", false, Some(20))]
+            });
+        };
+        let mut sort = AssetSort::default();
+        update(&mut table, sort, "/assets");
+        update(&mut table, sort, "/assets");
+        assert_eq!(names(table.rows()), ["A.png", "b.png"]);
+
+        sort.click(AssetSortColumn::Name);
+        update(&mut table, sort, "/assets");
+        assert_eq!(names(table.rows()), ["b.png", "A.png"]);
+        update(&mut table, sort, "/assets/textures");
+        table.invalidate();
+        update(&mut table, sort, "/assets/textures");
+        assert_eq!(builds, 4);
+    }
+
+    #[test]
+    fn sizes_and_times_are_formatted() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+        let modified = UNIX_EPOCH + Duration::from_secs(1_660_496_700);
+        assert_eq!(format_modified(modified), "2022-08-14 17:05");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::editor::assets::asset_loader::AssetDirectory;
+use crate::editor::assets::dependencies::AssetDependencyGraph;
+use crate::editor::assets::meta::AssetGuidIndex;
+use crate::editor::ui::preview::AssetPreview;
+use bevy::prelude::*;
+use bevy_egui::egui::{Id, ScrollArea, Window};
+use bevy_egui::EguiContext;
+use std::path::PathBuf;
+
+/// Plugin displaying results of "Find references" and "Show dependencies" actions
+pub struct DependencyViewPlugin;
+impl Plugin for DependencyViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DependencyView>()
+            .add_system(dependency_view_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyViewKind {
+    /// Scenes referencing the asset
+    References,
+    /// Assets referenced by the scene
+    Dependencies,
+}
+
+/// Resource containing the asset whose references or dependencies are displayed
+#[derive(Default)]
+pub struct DependencyView {
+    shown: Option<(DependencyViewKind, PathBuf)>,
+    results: Vec<PathBuf>,
+    /// Results are collected in the next frame, after the graph is refreshed
+    pending: bool,
+}
+
+impl DependencyView {
+    pub fn show(&mut self, kind: DependencyViewKind, path: PathBuf) {
+        self.shown = Some((kind, path));
+        self.pending = true;
+    }
+}
+
+/// Draws window listing references or dependencies of the asset. Clicking an entry previews it
+pub fn dependency_view_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut view: ResMut<DependencyView>,
+    mut graph: ResMut<AssetDependencyGraph>,
+    mut preview: ResMut<AssetPreview>,
+    root: Res<AssetDirectory>,
+    guids: Res<AssetGuidIndex>,
+) {
+    let (kind, path) = match view.shown.clone() {
+        Some(shown) => shown,
+        None => return,
+    };
+    if view.pending {
+        // Scenes may have been edited since the graph was built
+        graph.refresh(&root, &guids);
+        view.results = match kind {
+            DependencyViewKind::References => graph
+                .references(&path)
+                .into_iter()
+                .map(|scene| scene.to_path_buf())
+                .collect(),
+            DependencyViewKind::Dependencies => graph.dependencies(&path).to_vec(),
+        };
+        view.pending = false;
+    }
+
+    let name = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    let title = match kind {
+        DependencyViewKind::References => format!("References of {}", name),
+        DependencyViewKind::Dependencies => format!("Dependencies of {}", name),
+    };
+    let mut open = true;
+    Window::new(title)
+        .id(Id::new("DependencyView"))
+        .open(&mut open)
+        .show(egui_context.ctx_mut(), |ui| {
+            if view.results.is_empty() {
+                ui.label(match kind {
+                    DependencyViewKind::References => "No references found",
+                    DependencyViewKind::Dependencies => "No dependencies found",
+                });
+                return;
+            }
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for result in view.results.iter() {
+                    let label = result.strip_prefix(&root.path).unwrap_or(result);
+                    if ui.link(label.display().to_string()).clicked() {
+                        if let Some(asset) = root.find_asset(result) {
+                            preview.show(asset.clone());
+                        }
+                    }
+                }
+            });
+        });
+
+    if !open {
+        view.shown = None;
+        view.results.clear();
+    }
+}
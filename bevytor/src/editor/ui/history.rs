@@ -0,0 +1,66 @@
+use crate::editor::commands::{CommandQueue, UndoRedoCommandEvent};
+use bevy::prelude::*;
+use bevy_egui::egui::{Color32, RichText, ScrollArea, SelectableLabel, Window};
+use bevy_egui::EguiContext;
+
+/// Plugin displaying Undo/Redo chain stored in CommandQueue
+pub struct HistoryPanelPlugin;
+impl Plugin for HistoryPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HistoryPanelContext>()
+            .add_system(history_panel_system);
+    }
+}
+
+#[derive(Default)]
+pub struct HistoryPanelContext {
+    pub show_panel: bool,
+}
+
+/// Draws "History" window. Entries after the pointer can be redone and are greyed out.
+/// Clicking an entry undoes or redoes all the commands required to reach it
+pub fn history_panel_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut panel_context: ResMut<HistoryPanelContext>,
+    mut queue: ResMut<CommandQueue>,
+    mut commands_writer: EventWriter<UndoRedoCommandEvent>,
+) {
+    if !panel_context.show_panel {
+        return;
+    }
+
+    let mut target: Option<Option<usize>> = None;
+    Window::new("History")
+        .open(&mut panel_context.show_panel)
+        .default_width(250.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            ScrollArea::vertical()
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    if ui
+                        .add(SelectableLabel::new(
+                            queue.pointer.is_none(),
+                            "Initial state",
+                        ))
+                        .clicked()
+                    {
+                        target = Some(None);
+                    }
+
+                    for (index, item) in queue.items.iter().enumerate() {
+                        let mut text = RichText::new(item.describe());
+                        if queue.pointer < Some(index) {
+                            text = text.color(Color32::GRAY);
+                        }
+                        let selected = queue.pointer == Some(index);
+                        if ui.add(SelectableLabel::new(selected, text)).clicked() {
+                            target = Some(Some(index));
+                        }
+                    }
+                });
+        });
+
+    if let Some(target) = target {
+        queue.move_to(target, &mut commands_writer);
+    }
+}
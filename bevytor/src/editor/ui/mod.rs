@@ -1,2 +1,11 @@
 pub mod asset_browser;
+pub mod asset_navigation;
+pub mod asset_search;
+pub mod asset_selection;
+pub mod asset_table;
+pub mod dependencies;
+pub mod history;
+pub mod notifications;
+pub mod preview;
+pub mod scan_progress;
 pub mod widgets;
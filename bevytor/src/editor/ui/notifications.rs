@@ -0,0 +1,77 @@
+use crate::editor::commands::CommandFailedEvent;
+use bevy::prelude::*;
+use bevy_egui::egui::{Align2, Area, Color32, Frame, RichText};
+use bevy_egui::EguiContext;
+
+/// Plugin displaying short lived notifications, e.g. about failed commands
+pub struct NotificationsPlugin;
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorNotifications>()
+            .add_system(command_failed_notification_system)
+            .add_system(notifications_system.after(command_failed_notification_system));
+    }
+}
+
+/// How long notification stays on the screen, in seconds
+const NOTIFICATION_DURATION: f64 = 5.0;
+
+pub struct Notification {
+    pub text: String,
+    pub is_error: bool,
+    /// Time since startup after which notification is removed
+    expires_at: f64,
+}
+
+/// Resource containing currently displayed notifications
+#[derive(Default)]
+pub struct EditorNotifications {
+    pub items: Vec<Notification>,
+}
+
+impl EditorNotifications {
+    pub fn push(&mut self, text: String, is_error: bool, time: &Time) {
+        self.items.push(Notification {
+            text,
+            is_error,
+            expires_at: time.seconds_since_startup() + NOTIFICATION_DURATION,
+        });
+    }
+}
+
+pub fn command_failed_notification_system(
+    mut failures: EventReader<CommandFailedEvent>,
+    mut notifications: ResMut<EditorNotifications>,
+    time: Res<Time>,
+) {
+    for failure in failures.iter() {
+        notifications.push(failure.to_string(), true, &time);
+    }
+}
+
+/// Draws notifications stacked in the top right corner of the screen
+pub fn notifications_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut notifications: ResMut<EditorNotifications>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    notifications.items.retain(|item| item.expires_at > now);
+    if notifications.items.is_empty() {
+        return;
+    }
+
+    Area::new("Notifications")
+        .anchor(Align2::RIGHT_TOP, (-16.0, 32.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            for item in notifications.items.iter() {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    let mut text = RichText::new(&item.text);
+                    if item.is_error {
+                        text = text.color(Color32::LIGHT_RED);
+                    }
+                    ui.label(text);
+                });
+            }
+        });
+}
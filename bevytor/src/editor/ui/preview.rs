@@ -0,0 +1,379 @@
+use crate::editor::assets::asset_loader::AssetType;
+use crate::editor::assets::audio::AudioMetadataCache;
+use crate::editor::assets::import::{
+    read_import_settings, save_import_settings, AudioPlaybackSettings, ImageAddressMode,
+    ImageFilter, ImportSettings, ImportSettingsChangedEvent, ImportedAudio,
+};
+use crate::editor::assets::watcher::{asset_watcher_system, AssetMovedEvent, AssetRemovedEvent};
+use crate::editor::ui::notifications::EditorNotifications;
+use bevy::audio::AudioSink;
+use bevy::prelude::*;
+use bevy_egui::egui::{
+    Button, CollapsingHeader, ComboBox, DragValue, Grid, ScrollArea, TextEdit, Ui, Vec2, Window,
+};
+use bevy_egui::EguiContext;
+use rodio::cpal::traits::HostTrait;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Plugin displaying details of the asset clicked in the asset browser
+pub struct AssetPreviewPlugin;
+impl Plugin for AssetPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetPreview>()
+            .init_resource::<AudioMetadataCache>()
+            .init_resource::<AudioPlaybackSettings>()
+            .insert_resource(AudioOutputDevice::detect())
+            .add_event::<AssetRemovedEvent>()
+            .add_event::<AssetMovedEvent>()
+            .add_event::<ImportSettingsChangedEvent>()
+            .add_system(preview_asset_changes_system.after(asset_watcher_system))
+            .add_system(asset_preview_system.after(preview_asset_changes_system));
+    }
+}
+
+/// Maximum size of the image displayed in the preview
+const PREVIEW_IMAGE_SIZE: f32 = 256.0;
+/// Only the beginning of large text files is displayed
+const PREVIEW_TEXT_LIMIT: u64 = 64 * 1024;
+
+/// Resource containing currently previewed asset
+#[derive(Default)]
+pub struct AssetPreview {
+    pub asset: Option<AssetType>,
+    playing: Option<PlayingAudio>,
+    /// Content of the previewed text file, read once per previewed path
+    text: Option<(PathBuf, Result<String, String>)>,
+    /// Import settings being edited, saved to the sidecar on "Apply"
+    import_settings: Option<(PathBuf, ImportSettings)>,
+}
+
+struct PlayingAudio {
+    // Keeps the source alive until playback starts
+    _source: Handle<AudioSource>,
+    sink: Handle<AudioSink>,
+    /// Time since startup after which playback is considered finished
+    ends_at: Option<f64>,
+}
+
+impl AssetPreview {
+    pub fn show(&mut self, asset: AssetType) {
+        self.asset = Some(asset);
+    }
+
+    pub fn is_previewed(&self, path: &Path) -> bool {
+        self.asset
+            .as_ref()
+            .is_some_and(|asset| asset.get_path().starts_with(path))
+    }
+
+    fn stop(&mut self, sinks: Option<&Assets<AudioSink>>) {
+        let playing = self.playing.take();
+        let sink = playing.and_then(|playing| sinks?.get(&playing.sink));
+        if let Some(sink) = sink {
+            sink.stop();
+        }
+    }
+}
+
+/// Availability of the audio output. Headless runs (e.g. CI) have no output device, so audio
+/// preview is disabled there
+pub struct AudioOutputDevice {
+    pub available: bool,
+}
+
+impl AudioOutputDevice {
+    pub fn detect() -> Self {
+        Self {
+            available: rodio::cpal::default_host()
+                .default_output_device()
+                .is_some(),
+        }
+    }
+}
+
+/// Forget cached data of assets that were removed or moved on disk
+pub fn preview_asset_changes_system(
+    mut preview: ResMut<AssetPreview>,
+    mut audio_metadata: ResMut<AudioMetadataCache>,
+    mut removed_reader: EventReader<AssetRemovedEvent>,
+    mut moved_reader: EventReader<AssetMovedEvent>,
+    sinks: Option<Res<Assets<AudioSink>>>,
+) {
+    let changed = removed_reader
+        .iter()
+        .map(|event| &event.path)
+        .chain(moved_reader.iter().map(|event| &event.from));
+    for path in changed {
+        audio_metadata.invalidate(path);
+        if preview.is_previewed(path) {
+            preview.stop(sinks.as_deref());
+            preview.asset = None;
+            preview.text = None;
+            preview.import_settings = None;
+        }
+    }
+}
+
+/// Draws "Preview" window with details of the previewed asset
+#[allow(clippy::too_many_arguments)]
+pub fn asset_preview_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut preview: ResMut<AssetPreview>,
+    mut audio_metadata: ResMut<AudioMetadataCache>,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    output_device: Res<AudioOutputDevice>,
+    mut audio: ImportedAudio,
+    sinks: Option<Res<Assets<AudioSink>>>,
+    notifications: Option<ResMut<EditorNotifications>>,
+    mut settings_writer: EventWriter<ImportSettingsChangedEvent>,
+) {
+    let asset = match preview.asset.clone() {
+        Some(asset) => asset,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+    let finished = preview
+        .playing
+        .as_ref()
+        .and_then(|playing| playing.ends_at)
+        .is_some_and(|ends_at| ends_at < now);
+    if finished {
+        preview.playing = None;
+    }
+
+    let mut open = true;
+    let mut applied_settings = None;
+    Window::new("Preview")
+        .open(&mut open)
+        .default_width(280.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.heading(asset.get_name());
+            ui.label(asset.get_path().display().to_string());
+            ui.separator();
+            applied_settings = draw_import_settings(ui, &mut preview, &asset);
+
+            match &asset {
+                AssetType::Image(image) => {
+                    ui.image(image.egui_texture_id, Vec2::splat(PREVIEW_IMAGE_SIZE));
+                }
+                AssetType::Font(font) => match font.egui_texture_id {
+                    Some(texture_id) => {
+                        ui.image(texture_id, Vec2::splat(PREVIEW_IMAGE_SIZE));
+                    }
+                    None => {
+                        ui.label("Font couldn't be rendered");
+                    }
+                },
+                AssetType::Shader(shader) => draw_text(ui, &mut preview, &shader.path),
+                AssetType::Text(text) => draw_text(ui, &mut preview, &text.path),
+                AssetType::Audio(audio_asset) => {
+                    let duration = draw_audio_metadata(ui, &mut audio_metadata, &audio_asset.path);
+                    if !audio.is_available() || !output_device.available {
+                        ui.add_enabled(false, Button::new("Play"))
+                            .on_disabled_hover_text("No audio output device");
+                        return;
+                    }
+                    if preview.playing.is_some() {
+                        if ui.button("Stop").clicked() {
+                            preview.stop(sinks.as_deref());
+                        }
+                    } else if ui.button("Play").clicked() {
+                        let source: Handle<AudioSource> =
+                            asset_server.load(audio_asset.path.as_path());
+                        // Looped audio plays until stopped
+                        let ends_at = match audio.settings(&source).repeat {
+                            true => None,
+                            false => duration.map(|duration| now + duration),
+                        };
+                        if let Some(sink) = audio.play(source.clone()) {
+                            preview.playing = Some(PlayingAudio {
+                                sink,
+                                _source: source,
+                                ends_at,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        });
+
+    if let Some((path, settings)) = applied_settings {
+        match save_import_settings(&path, settings) {
+            Ok(()) => settings_writer.send(ImportSettingsChangedEvent { path }),
+            Err(e) => {
+                let text = format!("Failed to save import settings of {:?}: {}", path, e);
+                match notifications {
+                    Some(mut notifications) => notifications.push(text, true, &time),
+                    None => error!("{}", text),
+                }
+            }
+        }
+    }
+
+    if !open {
+        preview.stop(sinks.as_deref());
+        preview.asset = None;
+        preview.text = None;
+        preview.import_settings = None;
+    }
+}
+
+/// Draws import settings editor of the asset. Returns the settings when "Apply" was clicked
+fn draw_import_settings(
+    ui: &mut Ui,
+    preview: &mut AssetPreview,
+    asset: &AssetType,
+) -> Option<(PathBuf, ImportSettings)> {
+    let path = asset.get_path();
+    let cached = preview
+        .import_settings
+        .as_ref()
+        .is_some_and(|(cached_path, _)| *cached_path == path);
+    if !cached {
+        let settings = match read_import_settings(&path) {
+            ImportSettings::None => ImportSettings::default_for(asset),
+            settings => settings,
+        };
+        preview.import_settings = Some((path.clone(), settings));
+    }
+    let (_, settings) = preview.import_settings.as_mut().unwrap();
+    if *settings == ImportSettings::None {
+        return None;
+    }
+
+    let mut applied = None;
+    let mut reverted = false;
+    CollapsingHeader::new("Import settings")
+        .default_open(true)
+        .show(ui, |ui| {
+            Grid::new("ImportSettings")
+                .num_columns(2)
+                .show(ui, |ui| match settings {
+                    ImportSettings::Image(image) => {
+                        ui.label("Filter");
+                        ComboBox::from_id_source("ImageFilter")
+                            .selected_text(format!("{:?}", image.filter))
+                            .show_ui(ui, |ui| {
+                                for filter in [ImageFilter::Linear, ImageFilter::Nearest] {
+                                    ui.selectable_value(
+                                        &mut image.filter,
+                                        filter,
+                                        format!("{:?}", filter),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("Address mode");
+                        ComboBox::from_id_source("ImageAddressMode")
+                            .selected_text(format!("{:?}", image.address_mode))
+                            .show_ui(ui, |ui| {
+                                let modes = [
+                                    ImageAddressMode::ClampToEdge,
+                                    ImageAddressMode::Repeat,
+                                    ImageAddressMode::MirrorRepeat,
+                                ];
+                                for mode in modes {
+                                    ui.selectable_value(
+                                        &mut image.address_mode,
+                                        mode,
+                                        format!("{:?}", mode),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("sRGB");
+                        ui.checkbox(&mut image.srgb, "");
+                        ui.end_row();
+                        ui.label("Mipmaps");
+                        ui.checkbox(&mut image.mipmaps, "");
+                        ui.end_row();
+                    }
+                    ImportSettings::Audio(audio) => {
+                        ui.label("Loop");
+                        ui.checkbox(&mut audio.looped, "");
+                        ui.end_row();
+                    }
+                    ImportSettings::Model(model) => {
+                        ui.label("Scale");
+                        ui.add(
+                            DragValue::new(&mut model.scale)
+                                .speed(0.01)
+                                .clamp_range(0.001..=1000.0),
+                        );
+                        ui.end_row();
+                    }
+                    ImportSettings::None => {}
+                });
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    applied = Some((path.clone(), settings.clone()));
+                }
+                reverted = ui.button("Revert").clicked();
+            });
+        });
+    ui.separator();
+
+    if reverted {
+        preview.import_settings = None;
+    }
+    applied
+}
+
+/// Read beginning of the text file. Invalid UTF-8 sequences are replaced
+fn read_text(path: &Path) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.take(PREVIEW_TEXT_LIMIT)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Draws read-only content of the text file
+fn draw_text(ui: &mut Ui, preview: &mut AssetPreview, path: &Path) {
+    let cached = preview
+        .text
+        .as_ref()
+        .is_some_and(|(cached_path, _)| cached_path == path);
+    if !cached {
+        preview.text = Some((path.to_path_buf(), read_text(path)));
+    }
+    let (_, text) = preview.text.as_ref().unwrap();
+    match text {
+        Ok(text) => {
+            ScrollArea::both().max_height(400.0).show(ui, |ui| {
+                ui.add(TextEdit::multiline(&mut text.as_str()).code_editor());
+            });
+        }
+        Err(e) => {
+            ui.label(format!("Failed to read file: {}", e));
+        }
+    }
+}
+
+/// Draws metadata of the audio file and returns its duration in seconds if available
+fn draw_audio_metadata(ui: &mut Ui, cache: &mut AudioMetadataCache, path: &Path) -> Option<f64> {
+    match cache.get_or_read(path) {
+        Ok(metadata) => {
+            Grid::new("AudioMetadata").num_columns(2).show(ui, |ui| {
+                ui.label("Duration");
+                ui.label(format!("{:.2} s", metadata.duration.as_secs_f64()));
+                ui.end_row();
+                ui.label("Channels");
+                ui.label(metadata.channels.to_string());
+                ui.end_row();
+                ui.label("Sample rate");
+                ui.label(format!("{} Hz", metadata.sample_rate));
+                ui.end_row();
+            });
+            Some(metadata.duration.as_secs_f64())
+        }
+        Err(e) => {
+            ui.label(format!("Failed to read metadata: {}", e));
+            None
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::editor::assets::scan::AssetScan;
+use bevy::prelude::*;
+use bevy_egui::egui::{Align2, ProgressBar, Window};
+use bevy_egui::EguiContext;
+
+/// Plugin displaying progress of the initial asset scan
+pub struct ScanProgressPlugin;
+impl Plugin for ScanProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(scan_progress_system);
+    }
+}
+
+/// Draws progress bar while AssetScan is running
+pub fn scan_progress_system(mut egui_context: ResMut<EguiContext>, scan: Option<Res<AssetScan>>) {
+    let scan = match scan {
+        Some(scan) => scan,
+        None => return,
+    };
+
+    let (processed, discovered) = scan.progress();
+    // Total count is unknown until the whole directory is walked
+    let (progress, text) = match scan.walk_finished() {
+        true => (
+            processed as f32 / discovered.max(1) as f32,
+            format!("{} / {} files", processed, discovered),
+        ),
+        false => (0.0, format!("{} / {}+ files", processed, discovered)),
+    };
+    Window::new("Scanning assets")
+        .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.add(
+                ProgressBar::new(progress)
+                    .desired_width(300.0)
+                    .animate(!scan.walk_finished())
+                    .text(text),
+            );
+        });
+}
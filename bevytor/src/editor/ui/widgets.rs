@@ -18,7 +18,8 @@ pub struct Thumbnail {
 impl Widget for Thumbnail {
     fn ui(mut self, ui: &mut Ui) -> Response {
         let image = Image::new(self.texture_id, self.size);
-        let sense = Sense::click();
+        // Thumbnails can be dragged onto directories
+        let sense = Sense::click_and_drag();
         let frame = true;
         let selected = self.selected;
         let label_size = ui
@@ -113,23 +114,29 @@ pub fn thumbnail(ui: &mut Ui, label: String, size: Vec2, texture_id: TextureId)
         size,
         texture_id,
         selected: false,
-        ..Default::default()
     };
     ui.add(image_button)
 }
 
+/// Called with the response of every drawn directory, e.g. for attaching context menu
+pub type DirectoryDecorator<'a> = dyn FnMut(&mut Ui, Response, &AssetDirectory) + 'a;
+
 fn draw_directories(
     ui: &mut Ui,
     directory: &AssetDirectory,
     draw_assets: bool,
+    decorate: &mut DirectoryDecorator,
 ) -> Option<Selection> {
     for child in directory.children_directories.iter() {
         if child.children_directories.is_empty() {
-            if ui.button(child.get_name()).clicked() {
+            let response = ui.button(child.get_name());
+            let clicked = response.clicked();
+            decorate(ui, response, child);
+            if clicked {
                 return Some(Selection::Directory(child.get_path()));
             }
         } else {
-            if let Some(dir) = draw_directory_hierarchy(ui, child, draw_assets) {
+            if let Some(dir) = draw_directory_hierarchy(ui, child, draw_assets, decorate) {
                 return Some(dir);
             }
         }
@@ -152,6 +159,7 @@ pub fn draw_directory_hierarchy(
     ui: &mut Ui,
     asset_directory: &AssetDirectory,
     should_draw_assets: bool,
+    decorate: &mut DirectoryDecorator,
 ) -> Option<Selection> {
     let directory_name = asset_directory.get_name();
     let id = ui.make_persistent_id(&directory_name);
@@ -162,9 +170,12 @@ pub fn draw_directory_hierarchy(
             if response.clicked() {
                 new_selection = Some(Selection::Directory(asset_directory.get_path()));
             }
+            decorate(ui, response, asset_directory);
         })
         .body(|ui| {
-            if let Some(selection) = draw_directories(ui, asset_directory, should_draw_assets) {
+            if let Some(selection) =
+                draw_directories(ui, asset_directory, should_draw_assets, decorate)
+            {
                 new_selection = Some(selection);
             }
 